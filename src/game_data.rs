@@ -2,24 +2,502 @@
 //! 
 //! You could also store data associated with each human player here.
 //! We could also store the player's gamepad_id here.
+use raylib::prelude::*;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
-pub struct GameData {
+/// folder for the game's files inside the platform data directory.
+const DATA_DIR_NAME: &str = "raylib-framework-starter";
+/// file the high score is kept in, inside the data directory.
+const HIGH_SCORE_FILE: &str = "highscore.txt";
+
+/// where the high score is saved.
+pub fn high_score_path() -> PathBuf {
+    data_dir(|name| std::env::var_os(name)).join(HIGH_SCORE_FILE)
+}
+
+/// the directory the game saves its files in. This is a folder in %APPDATA% on Windows,
+/// ~/Library/Application Support on macOS and $XDG_DATA_HOME (default ~/.local/share) elsewhere.
+/// When those are not set the current directory is used. var reads an environment variable.
+fn data_dir(var: impl Fn(&str) -> Option<OsString>) -> PathBuf {
+    // relative paths are ignored, they would depend on where the game is started from.
+    let dir = |name: &str| var(name).map(PathBuf::from).filter(|path| path.is_absolute());
+
+    let base = if cfg!(windows) {
+        dir("APPDATA")
+    } else if cfg!(target_os = "macos") {
+        dir("HOME").map(|home| home.join("Library").join("Application Support"))
+    } else {
+        dir("XDG_DATA_HOME").or_else(|| dir("HOME").map(|home| home.join(".local").join("share")))
+    };
+
+    match base {
+        Some(base) => base.join(DATA_DIR_NAME),
+        None => PathBuf::new(),
+    }
+}
+
+/// read the saved high score.
+pub fn load_high_score() -> u32 {
+    load_high_score_from(&high_score_path())
+}
+
+/// save the high score to high_score_path().
+pub fn save_high_score(score: u32) {
+    save_high_score_to(&high_score_path(), score);
+}
+
+/// read a high score from a file. A missing or unreadable file counts as a high score of zero.
+pub fn load_high_score_from(path: &Path) -> u32 {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|text| text.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+/// save a high score to a file, creating its directory if needed. A failed write is reported but does not stop the game.
+pub fn save_high_score_to(path: &Path, score: u32) {
+    let result = match path.parent() {
+        Some(dir) => fs::create_dir_all(dir),
+        None => Ok(()),
+    };
+    if let Err(e) = result.and_then(|_| fs::write(path, score.to_string())) {
+        println!("Could not save the high score: {}", e);
+    }
+}
+
+/// sound effect files, relative to the directory the game is run from.
+const PICKUP_SOUND_FILE: &str = "resources/pickup.wav";
+const WIN_SOUND_FILE: &str = "resources/win.wav";
+
+/// load a sound, None if it cannot be loaded so the game runs without it.
+/// A missing file is skipped quietly since the sounds are optional, a broken one is reported.
+fn load_sound<'a>(audio: &'a RaylibAudio, path: &str) -> Option<Sound<'a>> {
+    if !Path::new(path).exists() {
+        return None;
+    }
+
+    match audio.new_sound(path) {
+        Ok(sound) => Some(sound),
+        Err(e) => {
+            println!("Could not load sound '{}': {:?}", path, e);
+            None
+        }
+    }
+}
+
+/// The game's sound effects. Each one is None if its file could not be loaded.
+/// The sounds borrow the audio device, so it has to live longer than them (main owns it).
+pub struct Sounds<'a> {
+    pickup: Option<Sound<'a>>,
+    win: Option<Sound<'a>>,
+}
+
+impl<'a> Sounds<'a> {
+    pub fn load(audio: &'a RaylibAudio) -> Self {
+        Self {
+            pickup: load_sound(audio, PICKUP_SOUND_FILE),
+            win: load_sound(audio, WIN_SOUND_FILE),
+        }
+    }
+}
+
+/// seconds allowed for a level when time attack is turned on from the menu.
+pub const TIME_ATTACK_LIMIT: f32 = 30.0;
+
+/// How hard a game session is. Scenes read this when they are created.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    /// the next difficulty, wrapping from Hard back to Easy. Used by the menu to cycle through them.
+    pub fn next(&self) -> Difficulty {
+        match self {
+            Difficulty::Easy => Difficulty::Normal,
+            Difficulty::Normal => Difficulty::Hard,
+            Difficulty::Hard => Difficulty::Easy,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+        }
+    }
+
+    /// number of points to collect in the chase game.
+    pub fn point_count(&self) -> usize {
+        match self {
+            Difficulty::Easy => 3,
+            Difficulty::Normal => 5,
+            Difficulty::Hard => 8,
+        }
+    }
+
+    /// player speed in the chase game, in pixels per second.
+    pub fn player_speed(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 350.0,
+            Difficulty::Normal => 300.0,
+            Difficulty::Hard => 250.0,
+        }
+    }
+
+    /// on hard the points run away from the player when it gets close.
+    pub fn fleeing_points(&self) -> bool {
+        *self == Difficulty::Hard
+    }
+}
+
+/// The things a player can do with the keyboard.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Action {
+    Up,
+    Down,
+    Left,
+    Right,
+    Pause,
+}
+
+/// The keys bound to each action. An action can have several keys, by default WASD and the arrow keys both move.
+pub struct InputBindings {
+    up: Vec<KeyboardKey>,
+    down: Vec<KeyboardKey>,
+    left: Vec<KeyboardKey>,
+    right: Vec<KeyboardKey>,
+    pause: Vec<KeyboardKey>,
+}
+
+impl InputBindings {
+    /// the keys bound to an action.
+    pub fn keys(&self, action: Action) -> &[KeyboardKey] {
+        match action {
+            Action::Up => &self.up,
+            Action::Down => &self.down,
+            Action::Left => &self.left,
+            Action::Right => &self.right,
+            Action::Pause => &self.pause,
+        }
+    }
+
+    /// replace the keys for an action, e.g. from a settings menu.
+    pub fn set_binding(&mut self, action: Action, keys: Vec<KeyboardKey>) {
+        match action {
+            Action::Up => self.up = keys,
+            Action::Down => self.down = keys,
+            Action::Left => self.left = keys,
+            Action::Right => self.right = keys,
+            Action::Pause => self.pause = keys,
+        }
+    }
+
+    /// the names of the keys bound to an action for on-screen hints, e.g. "P" or "P or ESCAPE".
+    /// Empty when nothing is bound.
+    pub fn key_names(&self, action: Action) -> String {
+        let names: Vec<String> = self.keys(action)
+            .iter()
+            .map(|key| format!("{:?}", key).trim_start_matches("KEY_").to_string())
+            .collect();
+        names.join(" or ")
+    }
+
+    /// true while any key bound to the action is held.
+    pub fn is_down(&self, rl: &RaylibHandle, action: Action) -> bool {
+        self.keys(action).iter().any(|key| rl.is_key_down(*key))
+    }
+
+    /// true on the frame any key bound to the action is pressed.
+    pub fn is_pressed(&self, rl: &RaylibHandle, action: Action) -> bool {
+        self.keys(action).iter().any(|key| rl.is_key_pressed(*key))
+    }
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        Self {
+            up: vec![KeyboardKey::KEY_W, KeyboardKey::KEY_UP],
+            down: vec![KeyboardKey::KEY_S, KeyboardKey::KEY_DOWN],
+            left: vec![KeyboardKey::KEY_A, KeyboardKey::KEY_LEFT],
+            right: vec![KeyboardKey::KEY_D, KeyboardKey::KEY_RIGHT],
+            pause: vec![KeyboardKey::KEY_P],
+        }
+    }
+}
+
+pub struct GameData<'a> {
     pub points: u32,
+    /// best score from any run. Starts at zero, main loads the saved one with load_high_score.
+    pub high_score: u32,
+    /// when the current level started and finished, used for the clear time.
+    pub level_start: Option<Instant>,
+    pub level_end: Option<Instant>,
+    /// total time spent paused during the current level, left out of the clear time.
+    pub paused_duration: Duration,
+    /// when the current pause began, None while not paused.
+    pub pause_started: Option<Instant>,
+    pub bindings: InputBindings,
+    pub difficulty: Difficulty,
+    /// name shown with the player's results, set from the menu.
+    pub player_name: String,
+    /// time attack, seconds the player has to collect every point. Toggled from the menu, None plays without a limit.
+    pub time_limit: Option<f32>,
+    /// seed for generating levels. Set it to replay the same layout (e.g. a daily challenge), None means random.
+    pub seed: Option<u64>,
+    /// current window size, updated every frame by the main loop.
     pub screen_width: i32,
     pub screen_height: i32,
+    /// how far (0.0 to 1.0) the current frame is between the previous and the latest update.
+    /// Scenes can draw at previous.lerp(current, render_alpha) for smooth movement with a fixed update step.
+    /// The SceneManager sets this from the time left over after its fixed update steps.
+    pub render_alpha: f32,
+    /// wait for the monitor refresh before showing a frame. Read once when the window is created.
+    pub vsync: bool,
+    /// frame rate cap, also used as a fallback when the driver ignores vsync. Read once when the window is created.
+    pub target_fps: u32,
+    /// draw the scenes into an offscreen texture that is then copied to the screen. Needed for full-frame effects.
+    pub render_to_texture: bool,
+    /// apply the scanline / CRT shader to the whole frame. This turns on the offscreen texture as well.
+    pub crt_effect: bool,
+    /// print scene stack changes to the console.
+    pub debug: bool,
+    /// show the FPS / frame time overlay, toggled with F3.
+    pub show_debug_overlay: bool,
+    /// None when there is no audio device, the play methods do nothing without it.
+    pub sounds: Option<Sounds<'a>>,
 }
 
-impl GameData {
+impl GameData<'_> {
     pub fn new(width: i32, heigth: i32) -> Self {
         Self {
             points: 0,
+            high_score: 0,
+            level_start: None,
+            level_end: None,
+            paused_duration: Duration::ZERO,
+            pause_started: None,
+            bindings: InputBindings::default(),
+            difficulty: Difficulty::Normal,
+            player_name: String::from("Player"),
+            time_limit: None,
+            seed: None,
             screen_width: width,
-            screen_height: heigth
+            screen_height: heigth,
+            render_alpha: 1.0,
+            vsync: true,
+            target_fps: 60,
+            render_to_texture: false,
+            crt_effect: false,
+            debug: false,
+            show_debug_overlay: false,
+            sounds: None,
+        }
+    }
+
+    /// play the sound for collecting a point.
+    pub fn play_pickup(&self) {
+        if let Some(sound) = self.sounds.as_ref().and_then(|sounds| sounds.pickup.as_ref()) {
+            sound.play();
+        }
+    }
+
+    /// play the sound for finishing a level.
+    pub fn play_win(&self) {
+        if let Some(sound) = self.sounds.as_ref().and_then(|sounds| sounds.win.as_ref()) {
+            sound.play();
         }
     }
 
+    /// start a fresh run: clears the score and the level timers. The high score, bindings and settings
+    /// (difficulty, name, seed, time limit) are kept. There is no player health to restore in the chase game.
+    pub fn reset(&mut self) {
+        self.points = 0;
+        self.level_start = None;
+        self.level_end = None;
+        self.paused_duration = Duration::ZERO;
+        self.pause_started = None;
+    }
+
     /// add one to the player's total points.
     pub fn score(&mut self) {
         self.points += 1;
     }
-}
\ No newline at end of file
+
+    /// start timing a new level.
+    pub fn start_level(&mut self) {
+        self.level_start = Some(Instant::now());
+        self.level_end = None;
+        self.paused_duration = Duration::ZERO;
+        self.pause_started = None;
+    }
+
+    /// stop the level timer.
+    pub fn complete_level(&mut self) {
+        self.resume_timer();
+        self.level_end = Some(Instant::now());
+    }
+
+    /// seconds the level took, not counting time spent paused. None until the level is started and completed.
+    pub fn get_elapsed_time(&self) -> Option<f32> {
+        match (self.level_start, self.level_end) {
+            (Some(start), Some(end)) => {
+                let elapsed = end.duration_since(start).saturating_sub(self.paused_duration);
+                Some(elapsed.as_secs_f32())
+            },
+            _ => None,
+        }
+    }
+
+    /// stop counting level time, e.g. while the pause menu is open.
+    pub fn pause_timer(&mut self) {
+        if self.pause_started.is_none() {
+            self.pause_started = Some(Instant::now());
+        }
+    }
+
+    /// continue counting level time, the time since pause_timer is added to paused_duration.
+    pub fn resume_timer(&mut self) {
+        if let Some(started) = self.pause_started.take() {
+            self.paused_duration += started.elapsed();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sounds_do_nothing_without_audio() {
+        let data = GameData::new(640, 480);
+        assert!(data.sounds.is_none());
+        data.play_pickup();
+        data.play_win();
+    }
+
+    #[test]
+    fn paused_time_is_left_out_of_elapsed_time() {
+        let mut data = GameData::new(640, 480);
+        let now = Instant::now();
+        // a level that started 10 seconds ago and was paused for the last 4.
+        data.level_start = Some(now - Duration::from_secs(10));
+        data.pause_started = Some(now - Duration::from_secs(4));
+
+        data.resume_timer();
+        data.complete_level();
+
+        let elapsed = data.get_elapsed_time().unwrap();
+        assert!((elapsed - 6.0).abs() < 0.1, "elapsed was {}", elapsed);
+    }
+
+    #[test]
+    fn elapsed_time_needs_a_finished_level() {
+        let mut data = GameData::new(640, 480);
+        assert_eq!(data.get_elapsed_time(), None);
+        data.start_level();
+        assert_eq!(data.get_elapsed_time(), None);
+    }
+
+    #[test]
+    fn reset_clears_the_run_but_keeps_persistent_data() {
+        let mut data = GameData::new(640, 480);
+        data.high_score = 42;
+        data.bindings.set_binding(Action::Pause, vec![KeyboardKey::KEY_ESCAPE]);
+        data.points = 7;
+        data.start_level();
+        data.pause_timer();
+        data.paused_duration = Duration::from_secs(3);
+        data.complete_level();
+
+        data.reset();
+
+        assert_eq!(data.points, 0);
+        assert_eq!(data.level_start, None);
+        assert_eq!(data.level_end, None);
+        assert_eq!(data.paused_duration, Duration::ZERO);
+        assert_eq!(data.pause_started, None);
+        assert_eq!(data.high_score, 42);
+        assert_eq!(data.bindings.keys(Action::Pause), &[KeyboardKey::KEY_ESCAPE]);
+    }
+
+    /// a file in the temp directory that is unique to this test and process.
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("raylib_framework_{}_{}.txt", name, std::process::id()))
+    }
+
+    #[test]
+    fn high_score_round_trip() {
+        let path = temp_path("high_score_round_trip");
+        save_high_score_to(&path, 123);
+        assert_eq!(load_high_score_from(&path), 123);
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn missing_high_score_file_is_zero() {
+        let path = temp_path("missing_high_score");
+        let _ = fs::remove_file(&path);
+        assert_eq!(load_high_score_from(&path), 0);
+    }
+
+    #[test]
+    fn harder_difficulties_have_more_and_faster_points() {
+        assert_eq!(Difficulty::Easy.point_count(), 3);
+        assert_eq!(Difficulty::Normal.point_count(), 5);
+        assert_eq!(Difficulty::Hard.point_count(), 8);
+
+        assert_eq!(Difficulty::Easy.player_speed(), 350.0);
+        assert_eq!(Difficulty::Normal.player_speed(), 300.0);
+        assert_eq!(Difficulty::Hard.player_speed(), 250.0);
+
+        assert!(!Difficulty::Easy.fleeing_points());
+        assert!(!Difficulty::Normal.fleeing_points());
+        assert!(Difficulty::Hard.fleeing_points());
+    }
+
+    #[test]
+    fn next_difficulty_wraps_around() {
+        assert_eq!(Difficulty::Easy.next(), Difficulty::Normal);
+        assert_eq!(Difficulty::Normal.next(), Difficulty::Hard);
+        assert_eq!(Difficulty::Hard.next(), Difficulty::Easy);
+    }
+
+    #[test]
+    fn saving_creates_missing_directories() {
+        let dir = std::env::temp_dir().join(format!("raylib_framework_save_dir_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("nested").join(HIGH_SCORE_FILE);
+
+        save_high_score_to(&path, 7);
+        assert_eq!(load_high_score_from(&path), 7);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn data_dir_falls_back_to_the_current_directory() {
+        assert_eq!(data_dir(|_| None), PathBuf::new());
+        // relative paths are not trusted.
+        assert_eq!(data_dir(|_| Some(OsString::from("relative"))), PathBuf::new());
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    #[test]
+    fn data_dir_prefers_xdg_data_home() {
+        let both = |name: &str| match name {
+            "XDG_DATA_HOME" => Some(OsString::from("/data")),
+            "HOME" => Some(OsString::from("/home/player")),
+            _ => None,
+        };
+        assert_eq!(data_dir(both), Path::new("/data").join(DATA_DIR_NAME));
+
+        let home_only = |name: &str| if name == "HOME" { Some(OsString::from("/home/player")) } else { None };
+        assert_eq!(data_dir(home_only), Path::new("/home/player/.local/share").join(DATA_DIR_NAME));
+    }
+}
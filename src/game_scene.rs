@@ -3,70 +3,166 @@
 //! This represents the chase game. Here we store information about the game world and the player's "character".
 
 use raylib::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 
-use crate::menu_scene::WinScene;
-use crate::scenes::{Scene, SceneSwitch};
-use crate::game_data::GameData;
+use crate::menu_scene::{GameOverScene, PauseScene, WinScene};
+use crate::scenes::{Scene, SceneError, SceneSwitch};
+use crate::game_data::{Action, GameData};
 use crate::utils::*;
 
+/// size of the player's circle.
+const PLAYER_RADIUS: f32 = 15.0;
+/// size of the point's circle as drawn.
+const POINT_RADIUS: f32 = 20.0;
+/// the player collects a point when its circle reaches this close to the point's center.
+const PICKUP_RADIUS: f32 = 10.0;
+
+/// how close the player can get before a fleeing point starts to run.
+const FLEE_RANGE: f32 = 150.0;
+/// speed of a fleeing point in pixels per second. Slower than the player so it can still be caught.
+const FLEE_SPEED: f32 = 180.0;
+
+/// keep a point inside the window.
+fn clamp_to_screen(point: &mut Vector2, width: i32, height: i32) {
+    point.x = point.x.clamp(0.0, width as f32);
+    point.y = point.y.clamp(0.0, height as f32);
+}
+
+/// the direction to move in for the held movement keys. Opposite keys cancel out.
+fn move_direction(left: bool, right: bool, up: bool, down: bool) -> Vector2 {
+    let mut direction = Vector2::zero();
+    if left {
+        direction += Vector2::new(-1.0, 0.0);
+    }
+    if right {
+        direction += Vector2::new(1.0, 0.0);
+    }
+    if up {
+        direction += Vector2::new(0.0, -1.0);
+    }
+    if down {
+        direction += Vector2::new(0.0, 1.0);
+    }
+
+    // use unit length so diagonal movement is not faster. A zero vector has no direction to normalize.
+    if direction.length_sqr() > 0.0 {
+        direction.normalized()
+    } else {
+        Vector2::zero()
+    }
+}
+
+/// the direction to move in, is_down tells which actions are held. Kept apart from raylib so tests can pass
+/// in their own key state.
+fn direction_from(is_down: impl Fn(Action) -> bool) -> Vector2 {
+    move_direction(is_down(Action::Left), is_down(Action::Right), is_down(Action::Up), is_down(Action::Down))
+}
+
 pub struct GameScene {
     points: Vec<Vector2>,
+    /// how many points the level started with, for the progress display.
+    total_points: usize,
     player_position: Vector2,
+    /// the position before the last update, used to interpolate the drawn position.
+    previous_position: Vector2,
     player_direction: Vector2,
-    player_speed: f32
+    player_speed: f32,
+    /// when true the scene plays itself (attract mode) and any input returns to the menu.
+    demo: bool,
+    /// when true the current point moves away from the player once the player is within FLEE_RANGE.
+    fleeing_points: bool,
+    /// seconds left in a time attack game, None when there is no limit.
+    time_left: Option<f32>,
 }
 
 impl GameScene {
     pub fn new(n: usize, width: i32, height: i32) -> Self {
+        Self::with_rng(n, width, height, &mut rand::rng())
+    }
+
+    /// a game whose points are generated from a seed, the same seed always gives the same points.
+    pub fn new_seeded(n: usize, width: i32, height: i32, seed: u64) -> Self {
+        Self::with_rng(n, width, height, &mut StdRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(n: usize, width: i32, height: i32, rng: &mut impl Rng) -> Self {
         let mut points = Vec::new();
         for _ in 0..n {
-            points.push(random_point(width, height));
+            points.push(random_point_seeded(rng, width, height));
         }
+        let start = Vector2::new((width/2) as f32, (height/2) as f32);
         Self { 
             points: points,
-            player_position: Vector2::new((width/2) as f32, (height/2) as f32),
+            total_points: n,
+            player_position: start,
+            previous_position: start,
             player_direction: Vector2::zero(),
-            player_speed: 300.0
+            player_speed: 300.0,
+            demo: false,
+            fleeing_points: false,
+            time_left: None,
         }
     }
+
+    /// create the chase game using the point count and player speed of the current difficulty, and the seed if one is set.
+    pub fn from_game_data(data: &GameData) -> Self {
+        let n = data.difficulty.point_count();
+        let mut scene = match data.seed {
+            Some(seed) => Self::new_seeded(n, data.screen_width, data.screen_height, seed),
+            None => Self::new(n, data.screen_width, data.screen_height),
+        };
+        scene.player_speed = data.difficulty.player_speed();
+        scene.fleeing_points = data.difficulty.fleeing_points();
+        scene.time_left = data.time_limit;
+        scene
+    }
+
+    /// a self-playing game used as the title screen attract mode.
+    pub fn demo(n: usize, width: i32, height: i32) -> Self {
+        let mut scene = Self::new(n, width, height);
+        scene.demo = true;
+        scene
+    }
 }
 
 impl Scene for GameScene {
-    fn on_enter(&mut self, _rl: &mut RaylibHandle, _data: &mut GameData) {
-        
+    fn name(&self) -> &str {
+        if self.demo { "Demo" } else { "Game" }
     }
 
-    fn handle_input(&mut self, _rl: &mut RaylibHandle, _data: &mut GameData) -> SceneSwitch {
-        
-        // set the intention to move in the given direction.
-        let mut direction = Vector2::zero();
-        if _rl.is_key_down(KeyboardKey::KEY_A) || 
-            _rl.is_key_down(KeyboardKey::KEY_LEFT) 
-        {
-            direction += Vector2::new(-1.0, 0.0);
-        }
-        
-        if _rl.is_key_down(KeyboardKey::KEY_D) || 
-            _rl.is_key_down(KeyboardKey::KEY_RIGHT) 
-        {
-            direction += Vector2::new(1.0, 0.0);
+    fn on_enter(&mut self, _rl: &mut RaylibHandle, data: &mut GameData) -> Result<(), SceneError> {
+        if !self.demo {
+            data.start_level();
         }
+        Ok(())
+    }
 
-        if _rl.is_key_down(KeyboardKey::KEY_W) || 
-            _rl.is_key_down(KeyboardKey::KEY_UP) 
-        {
-            direction += Vector2::new(0.0, -1.0);
-        }
+    fn handle_input(&mut self, _rl: &mut RaylibHandle, data: &mut GameData) -> SceneSwitch {
 
-        if _rl.is_key_down(KeyboardKey::KEY_S) || 
-            _rl.is_key_down(KeyboardKey::KEY_DOWN) 
-        {
-            direction += Vector2::new(0.0, 1.0);
-        }
+        if self.demo {
+            // any input ends the demo and goes back to the menu.
+            if any_input(_rl) {
+                return SceneSwitch::Pop;
+            }
 
-        direction.normalize();
+            // steer straight at the current point.
+            self.player_direction = match self.points.last() {
+                Some(target) => (*target - self.player_position).normalized(),
+                None => Vector2::zero(),
+            };
+            return SceneSwitch::None;
+        }
+        
+        if data.bindings.is_pressed(_rl, Action::Pause) {
+            // the game is still drawn under the pause menu. No updates run while paused, so drop the
+            // interpolation or the player would slide as render_alpha keeps changing.
+            self.previous_position = self.player_position;
+            return SceneSwitch::Push(Box::new(PauseScene::new()));
+        }
 
-        self.player_direction = direction;
+        // set the intention to move in the given direction.
+        self.player_direction = direction_from(|action| data.bindings.is_down(_rl, action));
 
         SceneSwitch::None
     }
@@ -75,18 +171,50 @@ impl Scene for GameScene {
 
         // update position of player, deal with collisions (later ...)
         let speed_delta = self.player_speed * _dt;
+        self.previous_position = self.player_position;
         self.player_position = self.player_position + self.player_direction * speed_delta;
 
+        // challenge mode, the point runs straight away from the player but cannot leave the screen.
+        if self.fleeing_points && let Some(last) = self.points.last_mut() {
+            let away = *last - self.player_position;
+            if away.length() < FLEE_RANGE {
+                *last += away.normalized() * (FLEE_SPEED * _dt);
+            }
+        }
+
+        // keep the points on screen. This stops a fleeing point at the edge and pulls points back in
+        // when the window is made smaller, so they can still be reached.
+        for point in self.points.iter_mut() {
+            clamp_to_screen(point, data.screen_width, data.screen_height);
+        }
 
         if let Some(last) = self.points.last() {
             // remove the last point.
-            if last.distance_to(self.player_position) < 25.0 {
+            if check_collision_circles(&self.player_position, PLAYER_RADIUS, last, PICKUP_RADIUS) {
                 self.points.pop();
-                data.score();
+                // the demo does not count towards the player's score.
+                if !self.demo {
+                    data.score();
+                    data.play_pickup();
+                }
             } 
+        } else if self.demo {
+            // demo finished, return to the menu.
+            return SceneSwitch::Pop;
         } else {
-            println!("Deal with win condition, send new scene");
-            return SceneSwitch::Push(Box::new(WinScene));
+            data.complete_level();
+            data.play_win();
+            // replace the game so a restart from the win screen does not leave the old game on the stack.
+            return SceneSwitch::Replace(Box::new(WinScene::new()));
+        }
+
+        // time attack, the game is lost when the clock runs out before the last point is collected.
+        if let Some(time_left) = self.time_left.as_mut() {
+            *time_left -= _dt;
+            if *time_left <= 0.0 {
+                data.complete_level();
+                return SceneSwitch::Replace(Box::new(GameOverScene::new()));
+            }
         }
 
 
@@ -96,24 +224,167 @@ impl Scene for GameScene {
     fn draw(&self, d: &mut RaylibDrawHandle, data: &mut GameData){
         d.clear_background(Color::WHITE);
 
-        // Draw player
-        d.draw_circle(self.player_position.x as i32,
-             self.player_position.y as i32, 
-             15.0, 
+        // Draw player between the last two updates
+        let drawn_position = vec2_lerp(self.previous_position, self.player_position, data.render_alpha);
+        d.draw_circle(drawn_position.x as i32,
+             drawn_position.y as i32, 
+             PLAYER_RADIUS, 
              Color::BLACK);
         
         // Draw last point in the vector
         if let Some(last) = self.points.last() {
             d.draw_circle(last.x as i32,
              last.y as i32, 
-            20.0, 
+            POINT_RADIUS, 
              Color::BLUE);
         }
 
+        if self.demo {
+            d.draw_text("DEMO - press any key", 10, 10, 20, Color::GRAY);
+            return;
+        }
+
         // Draw score based on game data
         let message = format!("Score: {}", data.points);
-        d.draw_text(message.as_str(), 10, data.screen_height - 25, 20, Color::BLACK);
+        draw_text_outlined(d, message.as_str(), 10, data.screen_height - 25, 20, Color::BLACK, Color::WHITE);
+        let collected = self.total_points - self.points.len();
+        let message = format!("Goals: {}/{}", collected, self.total_points);
+        draw_text_outlined(d, message.as_str(), 150, data.screen_height - 25, 20, Color::BLACK, Color::WHITE);
+
+        if let Some(time_left) = self.time_left {
+            let message = format!("Time left: {:.1}s", time_left.max(0.0));
+            draw_text_outlined(d, message.as_str(), 10, 10, 20, Color::RED, Color::WHITE);
+        }
+    }
+
+    /// hide the cursor while playing, the player is controlled with the keyboard.
+    fn wants_cursor(&self) -> bool {
+        false
     }
 
     fn on_exit(&mut self, _rl: &mut RaylibHandle, _data: &mut GameData) {}
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_data::{Difficulty, InputBindings};
+    use crate::scene_manager::FIXED_DT;
+
+    /// a game with one point far from the player, so it is not collected during the test.
+    fn timed_game(limit: f32) -> GameScene {
+        let mut scene = GameScene::new_seeded(1, 640, 480, 1);
+        scene.points = vec![Vector2::new(0.0, 0.0)];
+        scene.time_left = Some(limit);
+        scene
+    }
+
+    fn point_tuples(scene: &GameScene) -> Vec<(f32, f32)> {
+        scene.points.iter().map(|p| (p.x, p.y)).collect()
+    }
+
+    #[test]
+    fn seeded_games_repeat_their_points() {
+        let first = GameScene::new_seeded(5, 640, 480, 7);
+        let again = GameScene::new_seeded(5, 640, 480, 7);
+        let other = GameScene::new_seeded(5, 640, 480, 8);
+
+        assert_eq!(point_tuples(&first), point_tuples(&again));
+        assert_ne!(point_tuples(&first), point_tuples(&other));
+    }
+
+    #[test]
+    fn running_out_of_time_ends_the_game() {
+        let mut data = GameData::new(640, 480);
+        let mut scene = timed_game(1.0);
+
+        assert!(matches!(scene.update(0.5, &mut data), SceneSwitch::None));
+        assert!(matches!(scene.update(0.6, &mut data), SceneSwitch::Replace(_)));
+    }
+
+    #[test]
+    fn staying_under_the_limit_keeps_playing() {
+        let mut data = GameData::new(640, 480);
+        let mut scene = timed_game(1.0);
+
+        for _ in 0..9 {
+            assert!(matches!(scene.update(0.1, &mut data), SceneSwitch::None));
+        }
+    }
+
+    #[test]
+    fn no_limit_never_times_out() {
+        let mut data = GameData::new(640, 480);
+        let mut scene = timed_game(1.0);
+        scene.time_left = None;
+
+        assert!(matches!(scene.update(100.0, &mut data), SceneSwitch::None));
+    }
+
+    #[test]
+    fn diagonal_movement_has_unit_length() {
+        let direction = move_direction(false, true, false, true);
+        assert!((direction.length() - 1.0).abs() < 1e-6);
+        assert!((direction.x - direction.y).abs() < 1e-6);
+        assert!(direction.x > 0.0);
+    }
+
+    #[test]
+    fn no_keys_or_opposite_keys_do_not_move() {
+        assert_eq!(move_direction(false, false, false, false), Vector2::zero());
+        assert_eq!(move_direction(true, true, false, false), Vector2::zero());
+        assert_eq!(move_direction(true, true, true, true), Vector2::zero());
+    }
+
+    /// the move direction while only the held key is down.
+    fn direction_with(bindings: &InputBindings, held: KeyboardKey) -> Vector2 {
+        direction_from(|action| bindings.keys(action).contains(&held))
+    }
+
+    #[test]
+    fn movement_follows_bindings() {
+        let mut bindings = InputBindings::default();
+        assert_eq!(direction_with(&bindings, KeyboardKey::KEY_S), Vector2::new(0.0, 1.0));
+
+        // with up and down swapped, S moves up and W moves down.
+        bindings.set_binding(Action::Up, vec![KeyboardKey::KEY_S]);
+        bindings.set_binding(Action::Down, vec![KeyboardKey::KEY_W]);
+        assert_eq!(direction_with(&bindings, KeyboardKey::KEY_S), Vector2::new(0.0, -1.0));
+        assert_eq!(direction_with(&bindings, KeyboardKey::KEY_W), Vector2::new(0.0, 1.0));
+    }
+
+    #[test]
+    fn collecting_the_last_point_wins() {
+        let mut data = GameData::new(640, 480);
+        let mut scene = GameScene::new_seeded(3, 640, 480, 1);
+        // put every point under the player, one is collected per update.
+        scene.points = vec![scene.player_position; 3];
+
+        for collected in 1..=3 {
+            assert!(matches!(scene.update(FIXED_DT, &mut data), SceneSwitch::None));
+            assert_eq!(data.points, collected);
+            assert_eq!(scene.points.len(), 3 - collected as usize);
+        }
+        assert_eq!(scene.total_points, 3);
+
+        // the update after the last pickup moves on to the win screen.
+        match scene.update(FIXED_DT, &mut data) {
+            SceneSwitch::Replace(next) => assert_eq!(next.name(), "Win"),
+            _ => panic!("expected the win screen"),
+        }
+    }
+
+    #[test]
+    fn new_games_use_the_difficulty() {
+        let mut data = GameData::new(640, 480);
+        data.seed = Some(3);
+
+        for difficulty in [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard] {
+            data.difficulty = difficulty;
+            let scene = GameScene::from_game_data(&data);
+            assert_eq!(scene.points.len(), difficulty.point_count());
+            assert_eq!(scene.total_points, difficulty.point_count());
+            assert_eq!(scene.player_speed, difficulty.player_speed());
+            assert_eq!(scene.fleeing_points, difficulty.fleeing_points());
+        }
+    }
+}
@@ -3,6 +3,11 @@
 //! 
 pub mod game_data;
 pub mod scenes;
+pub mod scene_stack;
+pub mod scene_manager;
 pub mod game_scene;
 pub mod menu_scene;
+pub mod text_input_scene;
+pub mod render_target;
+pub mod shaders;
 pub mod utils;
@@ -1,37 +1,78 @@
-use raylib::prelude::*;
-
-use raylib_framework_testing::game_data::GameData;
+use raylib::prelude::RaylibAudio;
+use raylib_framework_testing::game_data::{load_high_score, GameData, Sounds};
 use raylib_framework_testing::menu_scene::MenuScene;
-use raylib_framework_testing::scenes::{Scene, SceneSwitch};
+use raylib_framework_testing::render_target::RenderTarget;
+use raylib_framework_testing::shaders::PostShader;
+use raylib_framework_testing::scene_manager::SceneManager;
 
 use std::time::Instant;
 
 fn main() {
     let width: i32 = 640;
     let height: i32 = 480;
-    let (mut rl, thread) = raylib::init()
-        .size(width, height)
-        .title("Raylib Framework Demo")
-        .build();
 
+    // the audio device is created before the game data, the sounds borrow it so it has to outlive them.
+    // Sound is optional, the game runs silently if there is no audio device.
+    let audio = match RaylibAudio::init_audio_device() {
+        Ok(audio) => Some(audio),
+        Err(e) => {
+            println!("Could not start audio, playing without sound: {:?}", e);
+            None
+        }
+    };
 
     // create the game data. This prepresents data associated with the human player.
+    // It also holds the settings, so it is created before the window that uses them.
     let mut game_data = GameData::new(width, height);
+    game_data.high_score = load_high_score();
+    if let Some(audio) = &audio {
+        game_data.sounds = Some(Sounds::load(audio));
+    }
 
-    // store scenes in a Vec. Box<dyn Scene> means a pointer to any type that implements the Scene trait.
-    let mut scenes: Vec<Box<dyn Scene>> = vec![Box::new(MenuScene)];
+    // Frame pacing, from game_data.vsync and game_data.target_fps.
+    // With vsync the GPU waits for the monitor refresh before showing a frame, this removes tearing and limits the
+    // frame rate to the refresh rate (often 60Hz). set_target_fps is a separate software limiter that sleeps between
+    // frames. It is always set: the vsync flag is only a hint that drivers can ignore, and then the target fps is the
+    // only thing stopping the loop from running as fast as possible. With a target at or above the refresh rate it
+    // does not slow a vsynced game down, a target below it caps the game lower.
+    let mut builder = raylib::init();
+    // the window can be resized, GameData's screen size is kept up to date in the main loop.
+    builder.size(width, height).title("Raylib Framework Demo").resizable();
+    if game_data.vsync {
+        // sets ConfigFlags::FLAG_VSYNC_HINT before the window is created.
+        builder.vsync();
+    }
+    let (mut rl, thread) = builder.build();
+    rl.set_target_fps(game_data.target_fps);
+
+    // the scene manager owns the stack of scenes and calls on_enter / on_exit as scenes are pushed and popped.
+    let mut scene_manager = match SceneManager::new(Box::new(MenuScene::new()), &mut rl, &mut game_data) {
+        Ok(manager) => manager,
+        Err(e) => {
+            println!("The first scene could not be started, exiting: {}", e);
+            return;
+        }
+    };
 
     // A variable for the time to calculate update steps in the game. Use for physics and animation.
     let mut last_time = Instant::now();
-    let mut keep_playing = true;
+
+    // offscreen texture used when game_data.render_to_texture is enabled.
+    let mut render_target = RenderTarget::new();
+    // full-screen shader used when game_data.crt_effect is enabled.
+    let mut crt_shader = PostShader::crt(&mut rl, &thread);
      
     // the main game / draw loop 
-    while !rl.window_should_close() && keep_playing {
+    while !rl.window_should_close() && !scene_manager.should_quit() {
         // update timing.
         let temp = Instant::now();
         let delta = (temp - last_time).as_secs_f32();
         last_time = temp;
 
+        // follow window resizes so scenes position their UI from the current size.
+        game_data.screen_width = rl.get_screen_width();
+        game_data.screen_height = rl.get_screen_height();
+
         // Below is a general framework for working with a game. Look at the Scene trait for some more information
         //
         // 1) preprocessing / setup. This was named on_enter. Called when the start is first started.
@@ -41,39 +82,20 @@ fn main() {
         // 4) draw the game elements
         // --- End of main loop ---
         // 5) postprocessing / clean up. This was named on_exit. Do any necessary clean up
-
-        let mut the_scene = scenes.last_mut().unwrap();
-
-        // handle user input. This 
-        //let result = scenes.last_mut().unwrap().handle_input(&mut rl, &mut game_data);
-        let result = the_scene.handle_input(&mut rl, &mut game_data);
-        match result {
-            SceneSwitch::Push(new_scene) => {
-                println!("got scene");
-                scenes.push(new_scene);
-            },
-            SceneSwitch::Quit => keep_playing = false,
-            _ => ()
-        }
-
-
-        let mut the_scene = scenes.last_mut().unwrap();
-        //let result = scenes.last_mut().unwrap().update(delta, &mut game_data);
-        let result = the_scene.update(delta, &mut game_data);
-        match result {
-            SceneSwitch::Push(new_scene) => {
-                println!("got scene");
-                scenes.push(new_scene);
-            },
-            SceneSwitch::Quit => keep_playing = false,
-            _ => ()
+        //
+        // The SceneManager runs steps 1 and 5 when scenes are switched, and 2 and 3 for the top scene here.
+        scene_manager.update(&mut rl, delta, &mut game_data);
+        if scene_manager.should_quit() {
+            break;
         }
 
+        // (re)create the offscreen texture if needed, this also follows window resizes.
+        render_target.update(&mut rl, &thread, game_data.render_to_texture || game_data.crt_effect);
 
         // Draw
-        let mut the_scene = scenes.last().unwrap();
         let mut d = rl.begin_drawing(&thread); 
-        the_scene.draw(&mut d, &mut  game_data);
+        let shader = if game_data.crt_effect { Some(&mut crt_shader) } else { None };
+        render_target.draw(&mut d, &thread, shader, |d| scene_manager.draw(d, &mut game_data));
 
     }
-}
\ No newline at end of file
+}
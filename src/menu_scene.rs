@@ -4,41 +4,156 @@
 use raylib::prelude::*;
 // use rand::{self, Rng};
 
-use crate::game_data::GameData;
+use crate::game_data::{save_high_score, Action, GameData, TIME_ATTACK_LIMIT};
 use crate::game_scene::GameScene;
-use crate::scenes::{Scene,SceneSwitch}; 
+use crate::scenes::{Scene, SceneError, SceneSwitch}; 
+use crate::text_input_scene::TextInputScene;
 use crate::utils::*;
 
+/// seconds the menu can sit without input before the demo starts playing.
+const ATTRACT_DELAY: f32 = 20.0;
+/// longest player name that can be typed.
+const MAX_NAME_LENGTH: usize = 12;
+
+/// start a fresh run with the same settings, replacing the win or game over screen.
+/// The game starts its own level timer in on_enter.
+fn play_again(data: &mut GameData) -> SceneSwitch {
+    data.reset();
+    SceneSwitch::Replace(Box::new(GameScene::from_game_data(data)))
+}
+
 /// A start screen or menu screen scene
-pub struct MenuScene;
+pub struct MenuScene {
+    /// time since the last input, used to start the attract mode demo.
+    idle_timer: Cooldown,
+    play_button: Button,
+    /// cycles through the difficulties, the label shows the current one.
+    difficulty_button: Button,
+    /// opens the text input to change the player's name.
+    name_button: Button,
+    /// turns the time attack limit on and off.
+    time_attack_button: Button,
+    /// selected button for gamepad navigation.
+    selection: MenuSelection,
+}
+
+impl MenuScene {
+    pub fn new() -> Self {
+        Self {
+            idle_timer: Cooldown::new(ATTRACT_DELAY),
+            play_button: Button::new(Rectangle::new(0.0, 0.0, 300.0, 100.0), "Chase Mode", Color::RED, Color::BLACK),
+            difficulty_button: Button::new(Rectangle::new(0.0, 0.0, 300.0, 50.0), "Difficulty", Color::LIGHTGRAY, Color::BLACK),
+            name_button: Button::new(Rectangle::new(0.0, 0.0, 300.0, 50.0), "Name", Color::LIGHTGRAY, Color::BLACK),
+            time_attack_button: Button::new(Rectangle::new(0.0, 0.0, 300.0, 50.0), "Time Attack", Color::LIGHTGRAY, Color::BLACK),
+            selection: MenuSelection::new(4),
+        }
+    }
+}
+
+impl Default for MenuScene {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MenuScene {
+    /// place the buttons around the center of the window.
+    fn layout(&mut self, data: &GameData) {
+        let center_x = data.screen_width as f32 / 2.0;
+        let center_y = data.screen_height as f32 / 2.0;
+        self.play_button.center_on(center_x, center_y - 50.0);
+        self.difficulty_button.center_on(center_x, center_y + 35.0);
+        self.name_button.center_on(center_x, center_y + 95.0);
+        self.time_attack_button.center_on(center_x, center_y + 155.0);
+    }
+
+    fn update_labels(&mut self, data: &GameData) {
+        self.difficulty_button.label = format!("Difficulty: {}", data.difficulty.name());
+        self.name_button.label = format!("Name: {}", data.player_name);
+        self.time_attack_button.label = match data.time_limit {
+            Some(limit) => format!("Time Attack: {}s", limit),
+            None => String::from("Time Attack: Off"),
+        };
+    }
+}
 
 impl Scene for MenuScene {
-    fn on_enter(&mut self, _rl: &mut RaylibHandle, _data: &mut GameData) {}
+    fn name(&self) -> &str {
+        "Menu"
+    }
+
+    fn on_enter(&mut self, _rl: &mut RaylibHandle, data: &mut GameData) -> Result<(), SceneError> {
+        self.update_labels(data);
+        self.layout(data);
+        Ok(())
+    }
+
+    fn on_resume(&mut self, _rl: &mut RaylibHandle, data: &mut GameData) {
+        // coming back from a game counts as activity, wait the full delay before the next demo.
+        self.idle_timer.reset();
+        // the name may have been changed by the text input scene.
+        self.update_labels(data);
+        self.layout(data);
+    }
 
     fn handle_input(&mut self, _rl: &mut RaylibHandle, data: &mut GameData) -> SceneSwitch {
+        // follow window resizes.
+        self.layout(data);
 
-        if _rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT) {
-            let click = _rl.get_mouse_position();
-            let rectangle = Rectangle::new(200.0, 200.0, 300.0, 150.0);
-            if  check_collision_point_rect(&click, &rectangle) {
-                println!("click");
-                return SceneSwitch::Push(Box::new(GameScene::new(5, data.screen_width, data.screen_height)));
-            }
+        let confirm = self.selection.handle_input(_rl, &data.bindings);
+
+        if self.play_button.is_clicked(_rl) || (confirm && self.selection.index() == 0) {
+            // a run left through the pause menu may have left points behind.
+            data.reset();
+            return SceneSwitch::Push(Box::new(GameScene::from_game_data(data)));
+        }
+
+        if self.difficulty_button.is_clicked(_rl) || (confirm && self.selection.index() == 1) {
+            data.difficulty = data.difficulty.next();
+            self.update_labels(data);
+        }
+
+        if self.name_button.is_clicked(_rl) || (confirm && self.selection.index() == 2) {
+            return SceneSwitch::Push(Box::new(TextInputScene::new("Enter your name", data.player_name.as_str(), MAX_NAME_LENGTH)));
+        }
+
+        if self.time_attack_button.is_clicked(_rl) || (confirm && self.selection.index() == 3) {
+            data.time_limit = match data.time_limit {
+                Some(_) => None,
+                None => Some(TIME_ATTACK_LIMIT),
+            };
+            self.update_labels(data);
+        }
+
+        if any_input(_rl) {
+            self.idle_timer.reset();
         }
         
         SceneSwitch::None
     }
 
-    fn update(&mut self, _dt: f32, _data: &mut GameData) -> SceneSwitch {
-        SceneSwitch::None
+    fn update(&mut self, _dt: f32, data: &mut GameData) -> SceneSwitch {
+        // arcade style attract mode, play a demo when nobody is using the menu.
+        if self.idle_timer.tick(_dt) {
+            return SceneSwitch::Push(Box::new(GameScene::demo(5, data.screen_width, data.screen_height)));
+        }
 
+        SceneSwitch::None
     }
 
     fn draw(&self, d: &mut RaylibDrawHandle, _data: &mut GameData) {
         d.clear_background(Color::WHITE);
         
-        d.draw_rectangle(200, 200, 300, 150, Color::RED);
-        d.draw_text("Click here", 210, 205, 20, Color::BLACK);
+        self.play_button.draw(d);
+        self.difficulty_button.draw(d);
+        self.name_button.draw(d);
+        self.time_attack_button.draw(d);
+        match self.selection.index() {
+            0 => self.play_button.draw_highlight(d),
+            1 => self.difficulty_button.draw_highlight(d),
+            2 => self.name_button.draw_highlight(d),
+            _ => self.time_attack_button.draw_highlight(d),
+        }
     }
 
     fn on_exit(&mut self, _rl: &mut RaylibHandle, _data: &mut GameData) {}
@@ -46,21 +161,70 @@ impl Scene for MenuScene {
 
 
 /// A win screen scene
-pub struct WinScene;
+pub struct WinScene {
+    /// true when this run beat the saved high score.
+    new_high_score: bool,
+    play_again_button: Button,
+    exit_button: Button,
+    /// selected button for gamepad navigation.
+    selection: MenuSelection,
+}
+
+impl WinScene {
+    pub fn new() -> Self {
+        Self {
+            new_high_score: false,
+            play_again_button: Button::new(Rectangle::new(0.0, 0.0, 300.0, 65.0), "Play Again", Color::GREEN, Color::BLACK),
+            exit_button: Button::new(Rectangle::new(0.0, 0.0, 300.0, 65.0), "Click here to exit.", Color::BLUE, Color::BLACK),
+            selection: MenuSelection::new(2),
+        }
+    }
+}
+
+impl WinScene {
+    /// place the buttons around the center of the window.
+    fn layout(&mut self, data: &GameData) {
+        let center_x = data.screen_width as f32 / 2.0;
+        let center_y = data.screen_height as f32 / 2.0;
+        self.play_again_button.center_on(center_x, center_y - 7.5);
+        self.exit_button.center_on(center_x, center_y + 77.5);
+    }
+}
+
+impl Default for WinScene {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl Scene for WinScene {
-    fn on_enter(&mut self, _rl: &mut RaylibHandle, _data: &mut GameData) {}
+    fn name(&self) -> &str {
+        "Win"
+    }
 
-    fn handle_input(&mut self, _rl: &mut RaylibHandle, _data: &mut GameData) -> SceneSwitch {
+    fn on_enter(&mut self, _rl: &mut RaylibHandle, data: &mut GameData) -> Result<(), SceneError> {
+        if data.points > data.high_score {
+            data.high_score = data.points;
+            save_high_score(data.high_score);
+            self.new_high_score = true;
+        }
+        self.layout(data);
+        Ok(())
+    }
 
-        if _rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT) {
-            let click = _rl.get_mouse_position();
-            let rectangle = Rectangle::new(200.0, 200.0, 300.0, 150.0);
-            if  check_collision_point_rect(&click, &rectangle) {
-                println!("click");
-                // close the program
-                return SceneSwitch::Quit;
-            }
+    fn handle_input(&mut self, _rl: &mut RaylibHandle, data: &mut GameData) -> SceneSwitch {
+        // follow window resizes.
+        self.layout(data);
+
+        let confirm = self.selection.handle_input(_rl, &data.bindings);
+
+        if self.play_again_button.is_clicked(_rl) || (confirm && self.selection.index() == 0) {
+            return play_again(data);
+        }
+
+        if self.exit_button.is_clicked(_rl) || (confirm && self.selection.index() == 1) {
+            // close the program
+            return SceneSwitch::Quit;
         }
         
         SceneSwitch::None
@@ -74,12 +238,229 @@ impl Scene for WinScene {
     fn draw(&self, d: &mut RaylibDrawHandle, _data: &mut GameData) {
         d.clear_background(Color::WHITE);
         
-        d.draw_rectangle(200, 200, 300, 150, Color::BLUE);
-        d.draw_text("You Win!", 210, 205, 20, Color::BLACK);
+        // the text sits above the buttons, both are centered in the window.
+        let center_x = _data.screen_width / 2;
+        let center_y = _data.screen_height / 2;
+        draw_text_centered(d, "You Win!", center_x, center_y - 170, 30, Color::BLACK);
         let message = format!("Final score: {}", _data.points);
-        d.draw_text(message.as_str(), 210, 225, 20, Color::BLACK);
-        d.draw_text("Click here to exit.", 210, 250, 20, Color::BLACK);
+        draw_text_centered(d, message.as_str(), center_x, center_y - 130, 20, Color::BLACK);
+        let time = format_time(_data.get_elapsed_time());
+        draw_text_centered(d, time.as_str(), center_x, center_y - 110, 20, Color::BLACK);
+
+        let message = format!("High score: {}", _data.high_score);
+        draw_text_centered(d, message.as_str(), center_x, center_y - 80, 20, Color::BLACK);
+        if self.new_high_score {
+            let message = format!("New high score by {}!", _data.player_name);
+            draw_text_centered(d, message.as_str(), center_x, center_y - 60, 20, Color::ORANGE);
+        }
+
+        self.play_again_button.draw(d);
+        self.exit_button.draw(d);
+        match self.selection.index() {
+            0 => self.play_again_button.draw_highlight(d),
+            _ => self.exit_button.draw_highlight(d),
+        }
     }
 
     fn on_exit(&mut self, _rl: &mut RaylibHandle, _data: &mut GameData) {}
 }
+
+
+/// A lose screen for when a time attack game runs out of time
+pub struct GameOverScene {
+    play_again_button: Button,
+    menu_button: Button,
+    /// selected button for gamepad navigation.
+    selection: MenuSelection,
+}
+
+impl GameOverScene {
+    pub fn new() -> Self {
+        Self {
+            play_again_button: Button::new(Rectangle::new(0.0, 0.0, 300.0, 65.0), "Play Again", Color::GREEN, Color::BLACK),
+            menu_button: Button::new(Rectangle::new(0.0, 0.0, 300.0, 65.0), "Back to menu", Color::RED, Color::BLACK),
+            selection: MenuSelection::new(2),
+        }
+    }
+}
+
+impl GameOverScene {
+    /// place the buttons around the center of the window.
+    fn layout(&mut self, data: &GameData) {
+        let center_x = data.screen_width as f32 / 2.0;
+        let center_y = data.screen_height as f32 / 2.0;
+        self.play_again_button.center_on(center_x, center_y - 7.5);
+        self.menu_button.center_on(center_x, center_y + 77.5);
+    }
+}
+
+impl Default for GameOverScene {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scene for GameOverScene {
+    fn name(&self) -> &str {
+        "GameOver"
+    }
+
+    fn on_enter(&mut self, _rl: &mut RaylibHandle, data: &mut GameData) -> Result<(), SceneError> {
+        self.layout(data);
+        Ok(())
+    }
+
+    fn handle_input(&mut self, _rl: &mut RaylibHandle, data: &mut GameData) -> SceneSwitch {
+        // follow window resizes.
+        self.layout(data);
+
+        let confirm = self.selection.handle_input(_rl, &data.bindings);
+
+        if self.play_again_button.is_clicked(_rl) || (confirm && self.selection.index() == 0) {
+            return play_again(data);
+        }
+
+        if self.menu_button.is_clicked(_rl) || (confirm && self.selection.index() == 1) {
+            // the game was replaced by this scene, so the menu is right below.
+            return SceneSwitch::Pop;
+        }
+
+        SceneSwitch::None
+    }
+
+    fn draw(&self, d: &mut RaylibDrawHandle, data: &mut GameData) {
+        d.clear_background(Color::WHITE);
+
+        let center_x = data.screen_width / 2;
+        let center_y = data.screen_height / 2;
+        draw_text_centered(d, "Time's up!", center_x, center_y - 140, 30, Color::RED);
+        let message = format!("Score: {}", data.points);
+        draw_text_centered(d, message.as_str(), center_x, center_y - 100, 20, Color::BLACK);
+
+        self.play_again_button.draw(d);
+        self.menu_button.draw(d);
+        match self.selection.index() {
+            0 => self.play_again_button.draw_highlight(d),
+            _ => self.menu_button.draw_highlight(d),
+        }
+    }
+}
+
+/// A pause menu drawn on top of the frozen game
+pub struct PauseScene {
+    resume_button: Button,
+    menu_button: Button,
+    /// selected button for gamepad navigation.
+    selection: MenuSelection,
+}
+
+impl PauseScene {
+    pub fn new() -> Self {
+        Self {
+            resume_button: Button::new(Rectangle::new(0.0, 0.0, 300.0, 65.0), "Resume", Color::GREEN, Color::BLACK),
+            menu_button: Button::new(Rectangle::new(0.0, 0.0, 300.0, 65.0), "Back to menu", Color::RED, Color::BLACK),
+            selection: MenuSelection::new(2),
+        }
+    }
+}
+
+impl PauseScene {
+    /// place the buttons around the center of the window.
+    fn layout(&mut self, data: &GameData) {
+        let center_x = data.screen_width as f32 / 2.0;
+        let center_y = data.screen_height as f32 / 2.0;
+        self.resume_button.center_on(center_x, center_y - 7.5);
+        self.menu_button.center_on(center_x, center_y + 77.5);
+    }
+}
+
+impl Default for PauseScene {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scene for PauseScene {
+    fn name(&self) -> &str {
+        "Pause"
+    }
+
+    fn on_enter(&mut self, _rl: &mut RaylibHandle, data: &mut GameData) -> Result<(), SceneError> {
+        // the clock should not run while the game is paused.
+        data.pause_timer();
+        self.layout(data);
+        Ok(())
+    }
+
+    fn handle_input(&mut self, _rl: &mut RaylibHandle, data: &mut GameData) -> SceneSwitch {
+        // follow window resizes.
+        self.layout(data);
+
+        let confirm = self.selection.handle_input(_rl, &data.bindings);
+
+        if data.bindings.is_pressed(_rl, Action::Pause)
+            || self.resume_button.is_clicked(_rl)
+            || (confirm && self.selection.index() == 0)
+        {
+            return SceneSwitch::Pop;
+        }
+
+        if self.menu_button.is_clicked(_rl) || (confirm && self.selection.index() == 1) {
+            // leave the game, pop this scene and the game below it.
+            return SceneSwitch::PopN(2);
+        }
+
+        SceneSwitch::None
+    }
+
+    fn update(&mut self, _dt: f32, _data: &mut GameData) -> SceneSwitch {
+        SceneSwitch::None
+    }
+
+    fn draw(&self, d: &mut RaylibDrawHandle, data: &mut GameData) {
+        // dim the game underneath instead of clearing the screen.
+        d.draw_rectangle(0, 0, data.screen_width, data.screen_height, Color::new(0, 0, 0, 160));
+
+        let center_x = data.screen_width / 2;
+        let center_y = data.screen_height / 2;
+        draw_text_centered(d, "Paused", center_x, center_y - 100, 30, Color::WHITE);
+        // the hint follows the pause binding, there is none when nothing is bound.
+        let keys = data.bindings.key_names(Action::Pause);
+        if !keys.is_empty() {
+            let hint = format!("Press {} to resume.", keys);
+            draw_text_centered(d, hint.as_str(), center_x, center_y - 65, 20, Color::WHITE);
+        }
+
+        self.resume_button.draw(d);
+        self.menu_button.draw(d);
+        match self.selection.index() {
+            0 => self.resume_button.draw_highlight(d),
+            _ => self.menu_button.draw_highlight(d),
+        }
+    }
+
+    fn draw_under(&self) -> bool {
+        true
+    }
+
+    fn on_exit(&mut self, _rl: &mut RaylibHandle, data: &mut GameData) {
+        data.resume_timer();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn play_again_resets_the_run_and_starts_a_game() {
+        let mut data = GameData::new(640, 480);
+        data.points = 4;
+
+        match play_again(&mut data) {
+            SceneSwitch::Replace(next) => assert_eq!(next.name(), "Game"),
+            _ => panic!("expected a new game"),
+        }
+        assert_eq!(data.points, 0);
+    }
+}
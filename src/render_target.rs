@@ -0,0 +1,82 @@
+//! Offscreen rendering for full-frame effects.
+//!
+//! Scenes keep drawing with the normal RaylibDrawHandle. RenderTarget::draw runs those draw calls in texture mode so
+//! they land in a RenderTexture2D, then copies (blits) that texture to the screen, which is where post-processing can happen.
+use raylib::prelude::*;
+
+use crate::shaders::PostShader;
+
+/// Holds the offscreen texture. The texture only exists while rendering to a texture is enabled.
+#[derive(Default)]
+pub struct RenderTarget {
+    texture: Option<RenderTexture2D>,
+}
+
+impl RenderTarget {
+    pub fn new() -> Self {
+        Self { texture: None }
+    }
+
+    /// true when the scenes are currently being drawn offscreen.
+    pub fn is_active(&self) -> bool {
+        self.texture.is_some()
+    }
+
+    /// create the texture when enabled, recreate it when the window changes size and free it when disabled.
+    /// Call this once per frame before begin_drawing.
+    pub fn update(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread, enabled: bool) {
+        if !enabled {
+            self.texture = None;
+            return;
+        }
+
+        let width = rl.get_screen_width();
+        let height = rl.get_screen_height();
+        let size_matches = match &self.texture {
+            Some(target) => target.texture.width == width && target.texture.height == height,
+            None => false,
+        };
+
+        if !size_matches {
+            match rl.load_render_texture(thread, width as u32, height as u32) {
+                Ok(target) => self.texture = Some(target),
+                Err(e) => {
+                    println!("Could not create the render texture, drawing directly to the screen: {}", e);
+                    self.texture = None;
+                }
+            }
+        }
+    }
+
+    /// draw a frame. draw_frame does the drawing, into the offscreen texture when it exists or straight to the screen
+    /// otherwise. The texture is then drawn to the screen, optionally through a post-processing shader.
+    pub fn draw(
+        &mut self,
+        d: &mut RaylibDrawHandle,
+        thread: &RaylibThread,
+        shader: Option<&mut PostShader>,
+        draw_frame: impl FnOnce(&mut RaylibDrawHandle),
+    ) {
+        let Some(target) = &mut self.texture else {
+            draw_frame(d);
+            return;
+        };
+
+        {
+            // texture mode ends when the guard is dropped at the end of this block.
+            let mut texture_mode = d.begin_texture_mode(thread, target);
+            draw_frame(&mut *texture_mode);
+        }
+
+        d.clear_background(Color::BLACK);
+        // OpenGL stores render textures upside down, a negative source height flips it back.
+        let source = Rectangle::new(0.0, 0.0, target.texture.width as f32, -target.texture.height as f32);
+        match shader {
+            Some(shader) => {
+                let mut s = d.begin_shader_mode(shader.shader_mut());
+                s.draw_texture_rec(target.texture(), source, Vector2::zero(), Color::WHITE);
+            }
+            None => d.draw_texture_rec(target.texture(), source, Vector2::zero(), Color::WHITE),
+        }
+    }
+}
@@ -0,0 +1,212 @@
+//! Owns the stack of scenes and runs the scene lifecycle.
+//!
+//! The scene on top of the stack receives input, updates and draws. The SceneSwitch values returned by the
+//! scene are passed to the SceneStack, which calls on_enter and on_exit as scenes are added and removed.
+use raylib::prelude::*;
+
+use crate::game_data::GameData;
+use crate::scene_stack::SceneStack;
+use crate::scenes::{Scene, SceneError, SceneSwitch};
+use crate::utils::draw_debug_overlay;
+
+/// length of one fixed update step in seconds.
+pub const FIXED_DT: f32 = 1.0 / 60.0;
+/// the most fixed steps run in one frame. After a long hitch the extra time is dropped instead of
+/// running ever more steps to catch up (the "spiral of death").
+pub const MAX_STEPS_PER_FRAME: u32 = 5;
+
+/// add dt to the time not simulated yet and work out how many fixed steps to run.
+/// Returns the number of steps and the time left over, which is always less than one step.
+/// When the step limit is hit the extra time is dropped.
+pub fn fixed_steps(accumulator: f32, dt: f32) -> (u32, f32) {
+    let mut accumulator = accumulator + dt;
+    let mut steps = 0;
+    while accumulator >= FIXED_DT && steps < MAX_STEPS_PER_FRAME {
+        accumulator -= FIXED_DT;
+        steps += 1;
+    }
+
+    if steps == MAX_STEPS_PER_FRAME {
+        accumulator %= FIXED_DT;
+    }
+    (steps, accumulator)
+}
+
+/// how far the leftover time is into the next fixed step, from 0.0 to 1.0. Drawing uses this to blend
+/// between the last two updates.
+pub fn render_alpha(accumulator: f32) -> f32 {
+    (accumulator / FIXED_DT).min(1.0)
+}
+
+/// turn the debug overlay on or off when its key (F3) was pressed this frame.
+fn toggle_debug_overlay(data: &mut GameData, pressed: bool) {
+    if pressed {
+        data.show_debug_overlay = !data.show_debug_overlay;
+    }
+}
+
+pub struct SceneManager {
+    /// the scene stack, the top scene is the active one.
+    stack: SceneStack<Box<dyn Scene>>,
+    /// whether the OS cursor is showing, so it is only toggled when the top scene changes its preference.
+    cursor_visible: bool,
+    /// real time that has passed but has not been simulated by a fixed update step yet.
+    accumulator: f32,
+}
+
+impl SceneManager {
+    /// start the first scene. Returns the scene's error if its on_enter fails.
+    pub fn new(first: Box<dyn Scene>, rl: &mut RaylibHandle, data: &mut GameData) -> Result<Self, SceneError> {
+        let mut manager = Self {
+            stack: SceneStack::new(first, rl, data)?,
+            cursor_visible: true,
+            accumulator: 0.0,
+        };
+        manager.update_cursor(rl);
+        Ok(manager)
+    }
+
+    /// true when a scene asked to quit or there are no scenes left to run.
+    pub fn should_quit(&self) -> bool {
+        self.stack.should_quit()
+    }
+
+    /// handle input once and update the top scene in fixed FIXED_DT steps, applying any scene switches they return.
+    /// dt is the real time since the last frame. Leftover time is kept for the next frame and
+    /// data.render_alpha is set to how far the frame is into the next step.
+    pub fn update(&mut self, rl: &mut RaylibHandle, dt: f32, data: &mut GameData) {
+        toggle_debug_overlay(data, rl.is_key_pressed(KeyboardKey::KEY_F3));
+
+        if let Some(scene) = self.stack.top_mut() {
+            let switch = scene.handle_input(rl, data);
+            self.apply_switch(switch, rl, data);
+        }
+
+        if self.should_quit() {
+            return;
+        }
+
+        let (steps, remaining) = fixed_steps(self.accumulator, dt);
+        self.accumulator = remaining;
+        for _ in 0..steps {
+            if let Some(scene) = self.stack.top_mut() {
+                let switch = scene.update(FIXED_DT, data);
+                let switched = !matches!(switch, SceneSwitch::None);
+                self.apply_switch(switch, rl, data);
+                // let the new top scene start on the next frame, after it has handled input.
+                // The steps not run are dropped, the new scene starts fresh.
+                if switched {
+                    break;
+                }
+            }
+        }
+        data.render_alpha = render_alpha(self.accumulator);
+
+        self.update_cursor(rl);
+    }
+
+    /// draw the top scene. Overlays that want a draw_under have the scenes below them drawn first, bottom to top.
+    /// The debug overlay is drawn last when it is turned on.
+    pub fn draw(&self, d: &mut RaylibDrawHandle, data: &mut GameData) {
+        let scenes = self.stack.scenes();
+        // walk down from the top to the deepest scene that is still visible.
+        let mut first = scenes.len().saturating_sub(1);
+        while first > 0 && scenes[first].draw_under() {
+            first -= 1;
+        }
+
+        for scene in &scenes[first..] {
+            scene.draw(d, data);
+        }
+
+        if data.show_debug_overlay && let Some(scene) = self.stack.top() {
+            draw_debug_overlay(d, scene.name());
+        }
+    }
+
+    /// the names of the scenes on the stack, from the top (active) scene to the bottom.
+    pub fn debug_stack(&self) -> Vec<&str> {
+        self.stack.debug_stack()
+    }
+
+    fn apply_switch(&mut self, switch: SceneSwitch, rl: &mut RaylibHandle, data: &mut GameData) {
+        if matches!(switch, SceneSwitch::None) {
+            return;
+        }
+
+        self.stack.apply(switch, rl, data);
+        if data.debug {
+            println!("Scene stack: {:?}", self.debug_stack());
+        }
+    }
+
+    /// show or hide the OS cursor based on the scene that is on top.
+    fn update_cursor(&mut self, rl: &mut RaylibHandle) {
+        let wants_cursor = match self.stack.top() {
+            Some(scene) => scene.wants_cursor(),
+            None => true,
+        };
+
+        if wants_cursor != self.cursor_visible {
+            if wants_cursor {
+                rl.show_cursor();
+            } else {
+                rl.hide_cursor();
+            }
+            self.cursor_visible = wants_cursor;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn long_frame_runs_the_step_limit() {
+        let (steps, remaining) = fixed_steps(0.0, 0.5);
+        assert_eq!(steps, MAX_STEPS_PER_FRAME);
+        assert!(remaining < FIXED_DT);
+    }
+
+    #[test]
+    fn leftover_time_is_kept() {
+        let (steps, remaining) = fixed_steps(0.0, FIXED_DT * 2.5);
+        assert_eq!(steps, 2);
+        assert!((remaining - FIXED_DT * 0.5).abs() < 1e-5);
+
+        // the leftover adds up to a step on a later frame.
+        let (steps, remaining) = fixed_steps(remaining, FIXED_DT * 0.75);
+        assert_eq!(steps, 1);
+        assert!((remaining - FIXED_DT * 0.25).abs() < 1e-5);
+    }
+
+    #[test]
+    fn short_frame_runs_no_steps() {
+        let (steps, remaining) = fixed_steps(0.0, FIXED_DT * 0.25);
+        assert_eq!(steps, 0);
+        assert!((remaining - FIXED_DT * 0.25).abs() < 1e-6);
+    }
+
+    #[test]
+    fn debug_overlay_toggles_on_each_press() {
+        let mut data = GameData::new(800, 450);
+        assert!(!data.show_debug_overlay);
+
+        toggle_debug_overlay(&mut data, false);
+        assert!(!data.show_debug_overlay);
+        toggle_debug_overlay(&mut data, true);
+        assert!(data.show_debug_overlay);
+        toggle_debug_overlay(&mut data, false);
+        assert!(data.show_debug_overlay);
+        toggle_debug_overlay(&mut data, true);
+        assert!(!data.show_debug_overlay);
+    }
+
+    #[test]
+    fn render_alpha_is_the_part_of_a_step_left_over() {
+        let (_, remaining) = fixed_steps(0.0, FIXED_DT * 2.5);
+        assert!((render_alpha(remaining) - 0.5).abs() < 1e-4);
+        assert_eq!(render_alpha(0.0), 0.0);
+    }
+}
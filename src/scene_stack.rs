@@ -0,0 +1,298 @@
+//! The stack of scenes and the rules for applying a SceneSwitch to it.
+//!
+//! SceneManager keeps its scenes in a SceneStack. The stack only needs the lifecycle calls from StackedScene,
+//! so it can be tested with simple stand-in scenes and no window.
+use raylib::prelude::*;
+
+use crate::game_data::GameData;
+use crate::scenes::{Scene, SceneError, SceneSwitch};
+
+/// The lifecycle calls the stack makes as scenes are added and removed.
+/// Context is what the calls get passed, the RaylibHandle for real scenes.
+pub trait StackedScene {
+    type Context;
+
+    fn name(&self) -> &str;
+
+    fn enter(&mut self, ctx: &mut Self::Context, data: &mut GameData) -> Result<(), SceneError>;
+
+    fn resume(&mut self, ctx: &mut Self::Context, data: &mut GameData);
+
+    fn exit(&mut self, ctx: &mut Self::Context, data: &mut GameData);
+}
+
+impl StackedScene for Box<dyn Scene> {
+    type Context = RaylibHandle;
+
+    fn name(&self) -> &str {
+        Scene::name(self.as_ref())
+    }
+
+    fn enter(&mut self, rl: &mut RaylibHandle, data: &mut GameData) -> Result<(), SceneError> {
+        Scene::on_enter(self.as_mut(), rl, data)
+    }
+
+    fn resume(&mut self, rl: &mut RaylibHandle, data: &mut GameData) {
+        Scene::on_resume(self.as_mut(), rl, data)
+    }
+
+    fn exit(&mut self, rl: &mut RaylibHandle, data: &mut GameData) {
+        Scene::on_exit(self.as_mut(), rl, data)
+    }
+}
+
+pub struct SceneStack<S> {
+    /// the last element is the active scene.
+    scenes: Vec<S>,
+    quit: bool,
+}
+
+impl<S: StackedScene> SceneStack<S> {
+    /// start the first scene. Returns the scene's error if it fails to enter.
+    pub fn new(mut first: S, ctx: &mut S::Context, data: &mut GameData) -> Result<Self, SceneError> {
+        first.enter(ctx, data)?;
+
+        Ok(Self {
+            scenes: vec![first],
+            quit: false,
+        })
+    }
+
+    /// true when a scene asked to quit or there are no scenes left to run.
+    pub fn should_quit(&self) -> bool {
+        self.quit || self.scenes.is_empty()
+    }
+
+    /// the scenes from the bottom to the top (active) scene.
+    pub fn scenes(&self) -> &[S] {
+        &self.scenes
+    }
+
+    pub fn top(&self) -> Option<&S> {
+        self.scenes.last()
+    }
+
+    pub fn top_mut(&mut self) -> Option<&mut S> {
+        self.scenes.last_mut()
+    }
+
+    /// the names of the scenes on the stack, from the top (active) scene to the bottom.
+    pub fn debug_stack(&self) -> Vec<&str> {
+        self.scenes.iter().rev().map(|scene| scene.name()).collect()
+    }
+
+    /// change the stack as the switch asks, calling enter, exit and resume on the scenes that come and go.
+    pub fn apply(&mut self, switch: SceneSwitch<S>, ctx: &mut S::Context, data: &mut GameData) {
+        match switch {
+            SceneSwitch::None => (),
+            SceneSwitch::Push(scene) => {
+                if let Some(scene) = Self::start(scene, ctx, data) {
+                    self.scenes.push(scene);
+                }
+            },
+            SceneSwitch::Replace(scene) => {
+                // start the new scene first, if it fails the current scene keeps running.
+                if let Some(scene) = Self::start(scene, ctx, data) {
+                    if let Some(mut old_scene) = self.scenes.pop() {
+                        old_scene.exit(ctx, data);
+                    }
+                    self.scenes.push(scene);
+                }
+            },
+            SceneSwitch::Pop => {
+                if let Some(mut old_scene) = self.scenes.pop() {
+                    old_scene.exit(ctx, data);
+                }
+                self.resume_top(ctx, data);
+            },
+            SceneSwitch::PopN(n) => {
+                // nothing is removed, so the top scene was never covered and is not resumed.
+                if n == 0 {
+                    return;
+                }
+                // clamp so popping more scenes than exist empties the stack, which makes should_quit true.
+                let count = n.min(self.scenes.len());
+                for _ in 0..count {
+                    if let Some(mut old_scene) = self.scenes.pop() {
+                        old_scene.exit(ctx, data);
+                    }
+                }
+                self.resume_top(ctx, data);
+            },
+            SceneSwitch::Quit => self.quit = true,
+        }
+    }
+
+    /// let the scene that was just uncovered know it is on top again.
+    fn resume_top(&mut self, ctx: &mut S::Context, data: &mut GameData) {
+        if let Some(scene) = self.scenes.last_mut() {
+            scene.resume(ctx, data);
+        }
+    }
+
+    /// run enter for a new scene. A scene that fails to start is dropped and the error is reported.
+    fn start(mut scene: S, ctx: &mut S::Context, data: &mut GameData) -> Option<S> {
+        match scene.enter(ctx, data) {
+            Ok(()) => Some(scene),
+            Err(e) => {
+                println!("Could not start scene: {}", e);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// the lifecycle calls made so far, shared by all the test scenes.
+    type Log = Rc<RefCell<Vec<String>>>;
+
+    /// a stand-in scene that records its lifecycle calls and can be set to fail on enter.
+    struct TestScene {
+        name: &'static str,
+        log: Log,
+        fail_enter: bool,
+    }
+
+    impl StackedScene for TestScene {
+        type Context = ();
+
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn enter(&mut self, _ctx: &mut (), _data: &mut GameData) -> Result<(), SceneError> {
+            self.log.borrow_mut().push(format!("enter {}", self.name));
+            if self.fail_enter {
+                return Err(SceneError::Setup(String::from("test failure")));
+            }
+            Ok(())
+        }
+
+        fn resume(&mut self, _ctx: &mut (), _data: &mut GameData) {
+            self.log.borrow_mut().push(format!("resume {}", self.name));
+        }
+
+        fn exit(&mut self, _ctx: &mut (), _data: &mut GameData) {
+            self.log.borrow_mut().push(format!("exit {}", self.name));
+        }
+    }
+
+    fn scene(name: &'static str, log: &Log) -> TestScene {
+        TestScene { name, log: log.clone(), fail_enter: false }
+    }
+
+    fn failing_scene(name: &'static str, log: &Log) -> TestScene {
+        TestScene { name, log: log.clone(), fail_enter: true }
+    }
+
+    /// a stack with the given scenes pushed in order, and the log cleared so tests only see what they do.
+    fn stack_of(names: &[&'static str], log: &Log, data: &mut GameData) -> SceneStack<TestScene> {
+        let mut stack = SceneStack::new(scene(names[0], log), &mut (), data).unwrap();
+        for name in &names[1..] {
+            stack.apply(SceneSwitch::Push(scene(name, log)), &mut (), data);
+        }
+        log.borrow_mut().clear();
+        stack
+    }
+
+    #[test]
+    fn push_replace_and_pop_run_the_lifecycle_in_order() {
+        let log = Log::default();
+        let mut data = GameData::new(800, 450);
+        let mut stack = SceneStack::new(scene("Menu", &log), &mut (), &mut data).unwrap();
+
+        stack.apply(SceneSwitch::Push(scene("Game", &log)), &mut (), &mut data);
+        stack.apply(SceneSwitch::Replace(scene("Win", &log)), &mut (), &mut data);
+        assert_eq!(stack.debug_stack(), vec!["Win", "Menu"]);
+
+        stack.apply(SceneSwitch::Pop, &mut (), &mut data);
+        assert_eq!(stack.debug_stack(), vec!["Menu"]);
+        assert_eq!(
+            *log.borrow(),
+            vec!["enter Menu", "enter Game", "enter Win", "exit Game", "exit Win", "resume Menu"]
+        );
+    }
+
+    #[test]
+    fn failed_replace_keeps_the_old_scene() {
+        let log = Log::default();
+        let mut data = GameData::new(800, 450);
+        let mut stack = stack_of(&["Menu", "Game"], &log, &mut data);
+
+        stack.apply(SceneSwitch::Replace(failing_scene("Win", &log)), &mut (), &mut data);
+        assert_eq!(stack.debug_stack(), vec!["Game", "Menu"]);
+        assert_eq!(*log.borrow(), vec!["enter Win"]);
+        assert!(!stack.should_quit());
+    }
+
+    #[test]
+    fn pop_n_exits_scenes_from_the_top() {
+        let log = Log::default();
+        let mut data = GameData::new(800, 450);
+        let mut stack = stack_of(&["Menu", "Game", "Pause"], &log, &mut data);
+
+        stack.apply(SceneSwitch::PopN(2), &mut (), &mut data);
+        assert_eq!(*log.borrow(), vec!["exit Pause", "exit Game", "resume Menu"]);
+        assert_eq!(stack.scenes().len(), 1);
+    }
+
+    #[test]
+    fn pop_n_zero_does_nothing() {
+        let log = Log::default();
+        let mut data = GameData::new(800, 450);
+        let mut stack = stack_of(&["Menu", "Game"], &log, &mut data);
+
+        stack.apply(SceneSwitch::PopN(0), &mut (), &mut data);
+        assert!(log.borrow().is_empty());
+        assert_eq!(stack.debug_stack(), vec!["Game", "Menu"]);
+    }
+
+    #[test]
+    fn pop_n_past_the_bottom_empties_the_stack() {
+        let log = Log::default();
+        let mut data = GameData::new(800, 450);
+        let mut stack = stack_of(&["Menu", "Game"], &log, &mut data);
+
+        stack.apply(SceneSwitch::PopN(5), &mut (), &mut data);
+        assert_eq!(*log.borrow(), vec!["exit Game", "exit Menu"]);
+        assert!(stack.scenes().is_empty());
+        assert!(stack.should_quit());
+    }
+
+    #[test]
+    fn pop_resumes_the_scene_below_once() {
+        let log = Log::default();
+        let mut data = GameData::new(800, 450);
+        let mut stack = stack_of(&["Game"], &log, &mut data);
+
+        stack.apply(SceneSwitch::Push(scene("Pause", &log)), &mut (), &mut data);
+        stack.apply(SceneSwitch::Pop, &mut (), &mut data);
+        let resumes = log.borrow().iter().filter(|call| *call == "resume Game").count();
+        assert_eq!(resumes, 1);
+    }
+
+    #[test]
+    fn replace_and_failed_push_do_not_resume() {
+        let log = Log::default();
+        let mut data = GameData::new(800, 450);
+        let mut stack = stack_of(&["Menu", "Game"], &log, &mut data);
+
+        stack.apply(SceneSwitch::Replace(scene("Win", &log)), &mut (), &mut data);
+        stack.apply(SceneSwitch::Push(failing_scene("Pause", &log)), &mut (), &mut data);
+        assert_eq!(*log.borrow(), vec!["enter Win", "exit Game", "enter Pause"]);
+        assert_eq!(stack.debug_stack(), vec!["Win", "Menu"]);
+    }
+
+    #[test]
+    fn debug_stack_lists_the_top_scene_first() {
+        let log = Log::default();
+        let mut data = GameData::new(800, 450);
+        let stack = stack_of(&["Menu", "Game", "Pause"], &log, &mut data);
+        assert_eq!(stack.debug_stack(), vec!["Pause", "Game", "Menu"]);
+    }
+}
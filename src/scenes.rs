@@ -1,20 +1,45 @@
 //! Traits for scenes and the scene switch signals.
 //! 
 use raylib::prelude::*;
+use std::fmt;
 
 use crate::game_data::GameData;
 ///
 /// The SceneSwitch enum was conceived with the help of ChatGPT 5.2
 /// 
 /// These values will signal to the manage that we need to change / update the scene
-pub enum SceneSwitch {
+///
+/// S is the scene type. It is always Box<dyn Scene> in the game, the scene stack tests use their own scenes.
+pub enum SceneSwitch<S = Box<dyn Scene>> {
     None,
-    Push(Box<dyn Scene>),
-    Replace(Box<dyn Scene>),
+    Push(S),
+    Replace(S),
     Pop,
+    /// pop several scenes at once, e.g. from a pause menu straight back to the title. Pops everything if n is larger than the stack.
+    PopN(usize),
     Quit,
 }
 
+/// Errors a scene can report from on_enter, for example when an asset it needs is missing.
+#[derive(Debug)]
+pub enum SceneError {
+    /// a file (texture, map, sound) could not be loaded. Holds the path and the reason.
+    AssetLoad { path: String, reason: String },
+    /// any other problem that stops the scene from starting.
+    Setup(String),
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneError::AssetLoad { path, reason } => write!(f, "failed to load '{}': {}", path, reason),
+            SceneError::Setup(message) => write!(f, "scene setup failed: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for SceneError {}
+
 ///
 /// The Scene trait was conceived with the help of ChatGPT 5.2
 /// 
@@ -23,8 +48,19 @@ pub enum SceneSwitch {
 /// The leading underscore tells the compiler not to complain (warn) if that variable is not read. 
 pub trait Scene {
     
-    /// called when the scene is first started.
-    fn on_enter(&mut self, _rl: &mut RaylibHandle, _data: &mut GameData) {}
+    /// a short name for the scene, used when printing the scene stack for debugging.
+    fn name(&self) -> &str {
+        "Scene"
+    }
+
+    /// called when the scene is first started. Return an error if the scene cannot run (e.g. a missing asset),
+    /// the scene will then not be added to the stack.
+    fn on_enter(&mut self, _rl: &mut RaylibHandle, _data: &mut GameData) -> Result<(), SceneError> {
+        Ok(())
+    }
+
+    /// called when the scene above this one is popped and this scene is on top again (e.g. after a pause menu closes).
+    fn on_resume(&mut self, _rl: &mut RaylibHandle, _data: &mut GameData) {}
 
     /// collects the player's intent from the controller / keyboard / input hardware.
     fn handle_input(&mut self, _rl: &mut RaylibHandle, _data: &mut GameData) -> SceneSwitch {
@@ -39,6 +75,16 @@ pub trait Scene {
     /// draw the scene elements. This should be very simple code that only draws using the RaylibDrawHandle
     fn draw(&self, d: &mut RaylibDrawHandle, data: &mut GameData);
 
+    /// return true for overlays (like a pause menu) so the scene below is drawn first and stays visible underneath.
+    fn draw_under(&self) -> bool {
+        false
+    }
+
+    /// return false to hide the OS mouse cursor while this scene is on top of the stack (e.g. during gameplay).
+    fn wants_cursor(&self) -> bool {
+        true
+    }
+
     /// called when the scene is finished. Do any clean up that is needed when the game ends (free textures or other data).
     /// Rust may take care of most of the memory clean up, but releasing GPU memory might go here.
     fn on_exit(&mut self, _rl: &mut RaylibHandle, _data: &mut GameData) {}
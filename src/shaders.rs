@@ -0,0 +1,91 @@
+//! Full-screen shaders applied when the offscreen render texture is copied to the screen.
+//!
+//! Ships a simple scanline / CRT effect. Only fragment shaders are needed, raylib supplies the default vertex shader.
+use raylib::prelude::*;
+
+/// Desktop OpenGL 3.3 uses GLSL 330. The web and mobile builds use OpenGL ES 2.0 which only understands GLSL 100,
+/// so the inputs, outputs and texture function are named differently.
+#[cfg(not(any(target_arch = "wasm32", target_os = "android")))]
+const GLSL_HEADER: &str = "#version 330
+in vec2 fragTexCoord;
+in vec4 fragColor;
+out vec4 finalColor;
+#define TEXTURE texture
+#define OUT_COLOR finalColor
+";
+
+#[cfg(any(target_arch = "wasm32", target_os = "android"))]
+const GLSL_HEADER: &str = "#version 100
+precision mediump float;
+varying vec2 fragTexCoord;
+varying vec4 fragColor;
+#define TEXTURE texture2D
+#define OUT_COLOR gl_FragColor
+";
+
+/// The scanline / CRT effect. Darkens every other line and bends the image like an old curved monitor.
+const CRT_BODY: &str = "
+uniform sampler2D texture0;
+uniform vec4 colDiffuse;
+
+// number of dark lines drawn across the screen.
+uniform float scanlineCount;
+// 0.0 = no scanlines, 1.0 = fully black lines.
+uniform float scanlineIntensity;
+// 0.0 = flat screen.
+uniform float curvature;
+
+void main()
+{
+    vec2 centered = fragTexCoord * 2.0 - 1.0;
+    centered *= 1.0 + curvature * dot(centered.yx, centered.yx);
+    vec2 uv = centered * 0.5 + 0.5;
+
+    if (uv.x < 0.0 || uv.x > 1.0 || uv.y < 0.0 || uv.y > 1.0) {
+        OUT_COLOR = vec4(0.0, 0.0, 0.0, 1.0);
+        return;
+    }
+
+    vec4 texel = TEXTURE(texture0, uv) * colDiffuse * fragColor;
+    float line = sin(uv.y * scanlineCount * 3.14159265) * 0.5 + 0.5;
+    texel.rgb *= 1.0 - scanlineIntensity * line;
+    OUT_COLOR = texel;
+}
+";
+
+/// A fragment shader used when drawing the offscreen texture to the screen.
+pub struct PostShader {
+    shader: Shader,
+}
+
+impl PostShader {
+    /// load a post-processing shader from the body of a fragment shader.
+    /// The body can use fragTexCoord, fragColor, TEXTURE(...) and must write to OUT_COLOR so it works with every GLSL version.
+    pub fn from_fragment(rl: &mut RaylibHandle, thread: &RaylibThread, body: &str) -> Self {
+        let source = format!("{}{}", GLSL_HEADER, body);
+        let shader = rl.load_shader_from_memory(thread, None, Some(source.as_str()));
+        Self { shader }
+    }
+
+    /// the sample scanline / CRT shader with some reasonable starting values.
+    pub fn crt(rl: &mut RaylibHandle, thread: &RaylibThread) -> Self {
+        let mut crt = Self::from_fragment(rl, thread, CRT_BODY);
+        crt.set_float("scanlineCount", 240.0);
+        crt.set_float("scanlineIntensity", 0.25);
+        crt.set_float("curvature", 0.05);
+        crt
+    }
+
+    /// set a float uniform by name. Unknown names are ignored (raylib reports a location of -1 for them).
+    pub fn set_float(&mut self, name: &str, value: f32) {
+        let location = self.shader.get_shader_location(name);
+        if location >= 0 {
+            self.shader.set_shader_value(location, value);
+        }
+    }
+
+    /// the raylib shader, used when drawing with begin_shader_mode.
+    pub fn shader_mut(&mut self) -> &mut Shader {
+        &mut self.shader
+    }
+}
@@ -0,0 +1,150 @@
+//! A scene for typing short text, like the player's name.
+//!
+//! The editing rules live in TextBuffer so they do not depend on raylib input.
+use raylib::prelude::*;
+
+use crate::game_data::GameData;
+use crate::scenes::{Scene, SceneSwitch};
+use crate::utils::*;
+
+/// Text being typed, limited to max_len characters.
+pub struct TextBuffer {
+    text: String,
+    max_len: usize,
+}
+
+impl TextBuffer {
+    /// a buffer starting with initial, cut to max_len characters.
+    pub fn new(initial: &str, max_len: usize) -> Self {
+        Self {
+            text: initial.chars().take(max_len).collect(),
+            max_len,
+        }
+    }
+
+    /// add a character. Control characters and characters past the length limit are ignored, returns false for those.
+    pub fn push(&mut self, c: char) -> bool {
+        if c.is_control() || self.text.chars().count() >= self.max_len {
+            return false;
+        }
+        self.text.push(c);
+        true
+    }
+
+    /// remove the last character, does nothing when empty.
+    pub fn backspace(&mut self) {
+        self.text.pop();
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+/// Asks for the player's name. Enter stores it in GameData::player_name and returns to the scene below.
+/// An empty name keeps the old one.
+pub struct TextInputScene {
+    prompt: String,
+    buffer: TextBuffer,
+    /// holding backspace keeps deleting.
+    backspace_repeat: KeyRepeat,
+}
+
+impl TextInputScene {
+    pub fn new(prompt: &str, initial: &str, max_len: usize) -> Self {
+        Self {
+            prompt: String::from(prompt),
+            buffer: TextBuffer::new(initial, max_len),
+            backspace_repeat: KeyRepeat::default(),
+        }
+    }
+}
+
+impl Scene for TextInputScene {
+    fn name(&self) -> &str {
+        "TextInput"
+    }
+
+    fn handle_input(&mut self, _rl: &mut RaylibHandle, data: &mut GameData) -> SceneSwitch {
+        // several characters can be typed in one frame, read them all.
+        while let Some(c) = _rl.get_char_pressed() {
+            self.buffer.push(c);
+        }
+
+        let backspace_down = _rl.is_key_down(KeyboardKey::KEY_BACKSPACE);
+        if self.backspace_repeat.update(backspace_down, _rl.get_frame_time()) {
+            self.buffer.backspace();
+        }
+
+        if _rl.is_key_pressed(KeyboardKey::KEY_ENTER) {
+            if !self.buffer.text().is_empty() {
+                data.player_name = String::from(self.buffer.text());
+            }
+            return SceneSwitch::Pop;
+        }
+
+        SceneSwitch::None
+    }
+
+    fn draw(&self, d: &mut RaylibDrawHandle, data: &mut GameData) {
+        d.clear_background(Color::WHITE);
+
+        // everything is placed from the window center so it follows resizes.
+        let center_x = data.screen_width / 2;
+        let center_y = data.screen_height / 2;
+        draw_text_centered(d, self.prompt.as_str(), center_x, center_y - 90, 30, Color::BLACK);
+
+        let text_box = Rectangle::new(center_x as f32 - 150.0, center_y as f32 - 40.0, 300.0, 50.0);
+        d.draw_rectangle_lines_ex(text_box, 2.0, Color::BLACK);
+        let text = format!("{}_", self.buffer.text());
+        draw_text_centered(d, text.as_str(), center_x, center_y - 15, 20, Color::BLACK);
+
+        draw_text_centered(d, "Press Enter when done.", center_x, center_y + 50, 20, Color::GRAY);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_appends_characters() {
+        let mut buffer = TextBuffer::new("", 10);
+        assert!(buffer.push('a'));
+        assert!(buffer.push('b'));
+        assert_eq!(buffer.text(), "ab");
+    }
+
+    #[test]
+    fn backspace_on_empty_does_nothing() {
+        let mut buffer = TextBuffer::new("", 10);
+        buffer.backspace();
+        assert_eq!(buffer.text(), "");
+
+        buffer.push('a');
+        buffer.backspace();
+        buffer.backspace();
+        assert_eq!(buffer.text(), "");
+    }
+
+    #[test]
+    fn push_stops_at_max_length() {
+        let mut buffer = TextBuffer::new("abc", 4);
+        assert!(buffer.push('d'));
+        assert!(!buffer.push('e'));
+        assert_eq!(buffer.text(), "abcd");
+
+        // a longer starting text is cut to the limit.
+        let buffer = TextBuffer::new("abcdef", 3);
+        assert_eq!(buffer.text(), "abc");
+    }
+
+    #[test]
+    fn push_rejects_control_characters() {
+        let mut buffer = TextBuffer::new("", 10);
+        assert!(!buffer.push('\n'));
+        assert!(!buffer.push('\u{8}'));
+        assert!(buffer.push(' '));
+        assert_eq!(buffer.text(), " ");
+    }
+}
@@ -2,6 +2,8 @@
 use raylib::prelude::*;
 use rand::Rng;
 
+use crate::game_data::{Action, InputBindings};
+
 pub fn check_collision_point_rect(point: &Vector2, rect: &Rectangle) -> bool {
     let in_x = point.x >= rect.x && point.x <= rect.x + rect.width;
     let in_y = point.y >= rect.y && point.y <= rect.y + rect.height;
@@ -9,11 +11,560 @@ pub fn check_collision_point_rect(point: &Vector2, rect: &Rectangle) -> bool {
     return in_x && in_y;
 }
 
+/// A clickable rectangle with a text label, used by the menu scenes.
+pub struct Button {
+    pub rect: Rectangle,
+    pub label: String,
+    pub background: Color,
+    pub text_color: Color,
+}
+
+impl Button {
+    pub fn new(rect: Rectangle, label: &str, background: Color, text_color: Color) -> Self {
+        Self {
+            rect,
+            label: String::from(label),
+            background,
+            text_color,
+        }
+    }
+
+    /// move the button so its center is at (center_x, center_y), keeping its size. Scenes use this to lay out
+    /// their buttons from the current window size.
+    pub fn center_on(&mut self, center_x: f32, center_y: f32) {
+        self.rect.x = center_x - self.rect.width / 2.0;
+        self.rect.y = center_y - self.rect.height / 2.0;
+    }
+
+    /// the center of the button, where its label is drawn.
+    pub fn center(&self) -> Vector2 {
+        Vector2::new(self.rect.x + self.rect.width / 2.0, self.rect.y + self.rect.height / 2.0)
+    }
+
+    /// true if the point is inside the button.
+    pub fn contains(&self, point: &Vector2) -> bool {
+        check_collision_point_rect(point, &self.rect)
+    }
+
+    /// true on the frame the left mouse button is pressed over the button.
+    pub fn is_clicked(&self, rl: &RaylibHandle) -> bool {
+        rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT) && self.contains(&rl.get_mouse_position())
+    }
+
+    /// draw a thick border around the button to show it is selected.
+    pub fn draw_highlight(&self, d: &mut RaylibDrawHandle) {
+        d.draw_rectangle_lines_ex(self.rect, 4.0, Color::BLACK);
+    }
+
+    pub fn draw(&self, d: &mut RaylibDrawHandle) {
+        d.draw_rectangle_rec(self.rect, self.background);
+        let center = self.center();
+        draw_text_centered(d, self.label.as_str(), center.x as i32, center.y as i32, 20, self.text_color);
+    }
+}
+
+/// clamp v to the range min..=max.
+pub fn clamp_f32(v: f32, min: f32, max: f32) -> f32 {
+    v.max(min).min(max)
+}
+
+/// blend from a to b, t = 0.0 gives a and t = 1.0 gives b. t is clamped to 0.0..=1.0.
+pub fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    let t = clamp_f32(t, 0.0, 1.0);
+    a + (b - a) * t
+}
+
+/// lerp for each component of a vector, t is clamped to 0.0..=1.0.
+pub fn vec2_lerp(a: Vector2, b: Vector2, t: f32) -> Vector2 {
+    Vector2::new(lerp(a.x, b.x, t), lerp(a.y, b.y, t))
+}
+
+/// true if the circle overlaps or touches the rectangle. Uses the point on the rectangle closest to the circle's center.
+pub fn check_collision_circle_rect(center: &Vector2, radius: f32, rect: &Rectangle) -> bool {
+    let closest_x = center.x.clamp(rect.x, rect.x + rect.width);
+    let closest_y = center.y.clamp(rect.y, rect.y + rect.height);
+    let dx = center.x - closest_x;
+    let dy = center.y - closest_y;
+
+    dx * dx + dy * dy <= radius * radius
+}
+
+/// true if the two circles overlap or touch. Compares squared distances to avoid a square root.
+pub fn check_collision_circles(center1: &Vector2, radius1: f32, center2: &Vector2, radius2: f32) -> bool {
+    let dx = center1.x - center2.x;
+    let dy = center1.y - center2.y;
+    let radii = radius1 + radius2;
+
+    dx * dx + dy * dy <= radii * radii
+}
+
+/// the gamepad that controls the menus.
+const MENU_GAMEPAD: i32 = 0;
+
+/// true on the frame the button is pressed on the menu gamepad. False when no gamepad is connected.
+pub fn is_gamepad_pressed(rl: &RaylibHandle, button: GamepadButton) -> bool {
+    rl.is_gamepad_available(MENU_GAMEPAD) && rl.is_gamepad_button_pressed(MENU_GAMEPAD, button)
+}
+
+/// seconds a key is held before it starts repeating.
+pub const KEY_REPEAT_DELAY: f32 = 0.4;
+/// seconds between repeats once a held key is repeating.
+pub const KEY_REPEAT_RATE: f32 = 0.1;
+
+/// Turns a held key into repeated presses: one press right away, nothing for initial_delay, then a press every repeat_rate.
+pub struct KeyRepeat {
+    initial_delay: f32,
+    repeat_rate: f32,
+    /// how long the key has been held, None while it is up.
+    held_time: Option<f32>,
+    /// held time at which the next repeat fires.
+    next_repeat: f32,
+}
+
+impl KeyRepeat {
+    pub fn new(initial_delay: f32, repeat_rate: f32) -> Self {
+        Self {
+            initial_delay,
+            repeat_rate,
+            held_time: None,
+            next_repeat: 0.0,
+        }
+    }
+
+    /// feed the key state for this frame and the frame time. Returns true on the frames that count as a press.
+    pub fn update(&mut self, is_down: bool, dt: f32) -> bool {
+        if !is_down {
+            self.held_time = None;
+            return false;
+        }
+
+        match self.held_time {
+            None => {
+                self.held_time = Some(0.0);
+                self.next_repeat = self.initial_delay;
+                true
+            },
+            Some(held) => {
+                let held = held + dt;
+                self.held_time = Some(held);
+                if held >= self.next_repeat {
+                    self.next_repeat += self.repeat_rate;
+                    true
+                } else {
+                    false
+                }
+            }
+        }
+    }
+}
+
+impl Default for KeyRepeat {
+    fn default() -> Self {
+        Self::new(KEY_REPEAT_DELAY, KEY_REPEAT_RATE)
+    }
+}
+
+/// Tracks the selected item of a vertical menu so it can be used without a mouse. Moving past either end wraps around.
+pub struct MenuSelection {
+    index: usize,
+    count: usize,
+    /// the up and down keys move again while held.
+    up_repeat: KeyRepeat,
+    down_repeat: KeyRepeat,
+}
+
+impl MenuSelection {
+    /// a selection over count items, starting at the first one.
+    pub fn new(count: usize) -> Self {
+        Self {
+            index: 0,
+            count,
+            up_repeat: KeyRepeat::default(),
+            down_repeat: KeyRepeat::default(),
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// select the item below, wrapping to the first.
+    pub fn next(&mut self) {
+        if self.count > 0 {
+            self.index = (self.index + 1) % self.count;
+        }
+    }
+
+    /// select the item above, wrapping to the last.
+    pub fn previous(&mut self) {
+        if self.count > 0 {
+            self.index = (self.index + self.count - 1) % self.count;
+        }
+    }
+
+    /// move with the d-pad and return true when the confirm button (A on Xbox, cross on PlayStation) is pressed.
+    pub fn handle_gamepad(&mut self, rl: &RaylibHandle) -> bool {
+        if is_gamepad_pressed(rl, GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_DOWN) {
+            self.next();
+        }
+        if is_gamepad_pressed(rl, GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_UP) {
+            self.previous();
+        }
+
+        is_gamepad_pressed(rl, GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_DOWN)
+    }
+
+    /// move with the keys bound to Up and Down, repeating while held, and return true when Enter is pressed.
+    pub fn handle_keyboard(&mut self, rl: &RaylibHandle, bindings: &InputBindings) -> bool {
+        self.navigate(|action| bindings.is_down(rl, action), rl.get_frame_time());
+        rl.is_key_pressed(KeyboardKey::KEY_ENTER)
+    }
+
+    /// move the selection from whether the Up and Down actions are held this frame. Kept apart from
+    /// raylib input so it can be tested.
+    pub fn navigate(&mut self, is_down: impl Fn(Action) -> bool, dt: f32) {
+        if self.down_repeat.update(is_down(Action::Down), dt) {
+            self.next();
+        }
+        if self.up_repeat.update(is_down(Action::Up), dt) {
+            self.previous();
+        }
+    }
+
+    /// handle both the gamepad and the keyboard, true when either confirms the selection.
+    pub fn handle_input(&mut self, rl: &RaylibHandle, bindings: &InputBindings) -> bool {
+        let gamepad_confirm = self.handle_gamepad(rl);
+        let keyboard_confirm = self.handle_keyboard(rl, bindings);
+        gamepad_confirm || keyboard_confirm
+    }
+}
+
 pub fn random_point(width: i32, height: i32) -> Vector2 {
     let mut rng = rand::rng();
 
+    random_point_seeded(&mut rng, width, height)
+}
+
+/// a random point drawn from the given generator. Use a seeded generator (e.g. StdRng::seed_from_u64)
+/// to get the same points every time.
+pub fn random_point_seeded(rng: &mut impl Rng, width: i32, height: i32) -> Vector2 {
     let x = rng.random_range(0..width);
     let y = rng.random_range(0..height);
 
     Vector2{x: x as f32, y: y as f32}
-}
\ No newline at end of file
+}
+
+/// true when any key or mouse button was pressed, or the mouse moved, this frame.
+pub fn any_input(rl: &mut RaylibHandle) -> bool {
+    let key = rl.get_key_pressed().is_some();
+    let click = rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT)
+        || rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_RIGHT);
+    let moved = rl.get_mouse_delta().length_sqr() > 0.0;
+
+    key || click || moved
+}
+
+/// draw text with a 1 pixel outline so it stays readable on any background.
+pub fn draw_text_outlined(d: &mut RaylibDrawHandle, text: &str, x: i32, y: i32, size: i32, color: Color, outline_color: Color) {
+    for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1), (-1, -1), (1, -1), (-1, 1), (1, 1)] {
+        d.draw_text(text, x + dx, y + dy, size, outline_color);
+    }
+    d.draw_text(text, x, y, size, color);
+}
+
+/// format a level time as "Time: 12.34s", or "Time: --" when there is no time to show.
+pub fn format_time(seconds: Option<f32>) -> String {
+    match seconds {
+        Some(seconds) => format!("Time: {:.2}s", seconds),
+        None => String::from("Time: --"),
+    }
+}
+
+/// the top-left position to draw text of the given measured width so it is centered on (center_x, center_y).
+/// The default font is as tall as the font size.
+pub fn centered_text_position(text_width: i32, center_x: i32, center_y: i32, size: i32) -> (i32, i32) {
+    (center_x - text_width / 2, center_y - size / 2)
+}
+
+/// draw text centered on a point, measuring the text so any length is centered.
+pub fn draw_text_centered(d: &mut RaylibDrawHandle, text: &str, center_x: i32, center_y: i32, size: i32, color: Color) {
+    let (x, y) = centered_text_position(d.measure_text(text, size), center_x, center_y, size);
+    d.draw_text(text, x, y, size, color);
+}
+
+/// draw the frame rate, frame time and scene name in the top right corner, on top of whatever is there.
+pub fn draw_debug_overlay(d: &mut RaylibDrawHandle, scene_name: &str) {
+    let lines = [
+        format!("FPS: {}", d.get_fps()),
+        format!("Frame: {:.2}ms", d.get_frame_time() * 1000.0),
+        format!("Scene: {}", scene_name),
+    ];
+
+    let x = d.get_screen_width() - 170;
+    d.draw_rectangle(x - 5, 5, 170, 70, Color::new(0, 0, 0, 160));
+    for (i, line) in lines.iter().enumerate() {
+        d.draw_text(line.as_str(), x, 10 + 20 * i as i32, 20, Color::LIME);
+    }
+}
+
+/// Counts down a duration and fires when it runs out, e.g. for timers, weapon cooldowns or attract mode.
+pub struct Cooldown {
+    duration: f32,
+    elapsed: f32,
+}
+
+impl Cooldown {
+    /// a cooldown that fires every duration seconds.
+    pub fn new(duration: f32) -> Self {
+        Self { duration, elapsed: 0.0 }
+    }
+
+    /// advance the cooldown by dt seconds. Returns true when it runs out and starts it over,
+    /// keeping the extra time so repeated cycles do not drift.
+    pub fn tick(&mut self, dt: f32) -> bool {
+        self.elapsed += dt;
+        if self.is_ready() {
+            self.elapsed -= self.duration;
+            return true;
+        }
+        false
+    }
+
+    /// true when the full duration has passed since the last reset.
+    pub fn is_ready(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+
+    /// start counting from zero again.
+    pub fn reset(&mut self) {
+        self.elapsed = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// feed a held key in dt steps and return the held times at which a press fired.
+    fn press_times(repeat: &mut KeyRepeat, dt: f32, frames: usize) -> Vec<f32> {
+        let mut times = Vec::new();
+        for frame in 0..frames {
+            if repeat.update(true, dt) {
+                times.push(frame as f32 * dt);
+            }
+        }
+        times
+    }
+
+    #[test]
+    fn key_repeat_fires_after_delay_then_at_rate() {
+        // values that are exact in binary, so the timing is not thrown off by rounding.
+        let mut repeat = KeyRepeat::new(0.5, 0.25);
+        assert_eq!(press_times(&mut repeat, 0.125, 9), vec![0.0, 0.5, 0.75, 1.0]);
+    }
+
+    #[test]
+    fn key_repeat_resets_on_release() {
+        let mut repeat = KeyRepeat::new(0.5, 0.25);
+        assert!(repeat.update(true, 0.125));
+        assert!(!repeat.update(true, 0.125));
+
+        assert!(!repeat.update(false, 0.125));
+        // pressing again fires right away and waits the full delay again.
+        assert_eq!(press_times(&mut repeat, 0.125, 5), vec![0.0, 0.5]);
+    }
+
+    #[test]
+    fn cooldown_fires_at_duration() {
+        let mut cooldown = Cooldown::new(1.0);
+        assert!(!cooldown.tick(0.25));
+        assert!(!cooldown.tick(0.25));
+        assert!(!cooldown.tick(0.25));
+        assert!(cooldown.tick(0.25));
+        assert!(!cooldown.is_ready());
+    }
+
+    #[test]
+    fn cooldown_keeps_leftover_time() {
+        let mut cooldown = Cooldown::new(1.0);
+        assert!(cooldown.tick(1.5));
+        // half a second was carried over, so half a second more fires it again.
+        assert!(cooldown.tick(0.5));
+    }
+
+    #[test]
+    fn cooldown_repeats_over_cycles() {
+        let mut cooldown = Cooldown::new(1.0);
+        let fired: Vec<usize> = (1..=12).filter(|_| cooldown.tick(0.25)).collect();
+        assert_eq!(fired, vec![4, 8, 12]);
+    }
+
+    #[test]
+    fn cooldown_reset_starts_over() {
+        let mut cooldown = Cooldown::new(1.0);
+        assert!(!cooldown.tick(0.75));
+        cooldown.reset();
+        assert!(!cooldown.tick(0.5));
+        assert!(cooldown.tick(0.5));
+    }
+
+    #[test]
+    fn lerp_endpoints_and_midpoint() {
+        assert_eq!(lerp(2.0, 6.0, 0.0), 2.0);
+        assert_eq!(lerp(2.0, 6.0, 1.0), 6.0);
+        assert_eq!(lerp(2.0, 6.0, 0.5), 4.0);
+    }
+
+    #[test]
+    fn lerp_clamps_t() {
+        assert_eq!(lerp(2.0, 6.0, -1.0), 2.0);
+        assert_eq!(lerp(2.0, 6.0, 2.0), 6.0);
+    }
+
+    #[test]
+    fn clamp_f32_limits_the_range() {
+        assert_eq!(clamp_f32(-1.0, 0.0, 10.0), 0.0);
+        assert_eq!(clamp_f32(5.0, 0.0, 10.0), 5.0);
+        assert_eq!(clamp_f32(11.0, 0.0, 10.0), 10.0);
+    }
+
+    #[test]
+    fn vec2_lerp_blends_each_component() {
+        let a = Vector2::new(0.0, 10.0);
+        let b = Vector2::new(4.0, 20.0);
+
+        let start = vec2_lerp(a, b, 0.0);
+        assert_eq!((start.x, start.y), (0.0, 10.0));
+        let middle = vec2_lerp(a, b, 0.5);
+        assert_eq!((middle.x, middle.y), (2.0, 15.0));
+        let end = vec2_lerp(a, b, 1.0);
+        assert_eq!((end.x, end.y), (4.0, 20.0));
+
+        let before = vec2_lerp(a, b, -0.5);
+        assert_eq!((before.x, before.y), (0.0, 10.0));
+        let after = vec2_lerp(a, b, 1.5);
+        assert_eq!((after.x, after.y), (4.0, 20.0));
+    }
+
+    #[test]
+    fn circle_rect_collision() {
+        let rect = Rectangle::new(0.0, 0.0, 10.0, 10.0);
+        // overlapping, the center is inside the rectangle.
+        assert!(check_collision_circle_rect(&Vector2::new(5.0, 5.0), 1.0, &rect));
+        // touching the right edge.
+        assert!(check_collision_circle_rect(&Vector2::new(12.0, 5.0), 2.0, &rect));
+        // separate.
+        assert!(!check_collision_circle_rect(&Vector2::new(13.0, 5.0), 2.0, &rect));
+        // near a corner but outside it, the closest point is the corner.
+        assert!(!check_collision_circle_rect(&Vector2::new(12.0, 12.0), 2.0, &rect));
+    }
+
+    #[test]
+    fn circles_collision() {
+        let center = Vector2::new(0.0, 0.0);
+        // overlapping.
+        assert!(check_collision_circles(&center, 2.0, &Vector2::new(3.0, 0.0), 2.0));
+        // touching.
+        assert!(check_collision_circles(&center, 2.0, &Vector2::new(3.0, 4.0), 3.0));
+        // separate.
+        assert!(!check_collision_circles(&center, 2.0, &Vector2::new(3.0, 4.0), 2.0));
+    }
+
+    #[test]
+    fn centered_text_position_from_width() {
+        assert_eq!(centered_text_position(100, 320, 240, 20), (270, 230));
+        assert_eq!(centered_text_position(0, 50, 50, 10), (50, 45));
+    }
+
+    #[test]
+    fn format_time_with_and_without_a_time() {
+        assert_eq!(format_time(Some(12.345)), "Time: 12.35s");
+        assert_eq!(format_time(Some(3.0)), "Time: 3.00s");
+        assert_eq!(format_time(None), "Time: --");
+    }
+
+    /// n points from a generator seeded with seed, as tuples so they can be compared.
+    fn seeded_points(seed: u64, n: usize) -> Vec<(f32, f32)> {
+        use rand::SeedableRng;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+        (0..n).map(|_| random_point_seeded(&mut rng, 640, 480)).map(|p| (p.x, p.y)).collect()
+    }
+
+    #[test]
+    fn same_seed_gives_same_points() {
+        assert_eq!(seeded_points(42, 10), seeded_points(42, 10));
+        assert_ne!(seeded_points(42, 10), seeded_points(43, 10));
+    }
+
+    #[test]
+    fn menu_selection_wraps_around() {
+        let mut selection = MenuSelection::new(3);
+        assert_eq!(selection.index(), 0);
+
+        selection.previous();
+        assert_eq!(selection.index(), 2);
+        selection.next();
+        assert_eq!(selection.index(), 0);
+        selection.next();
+        selection.next();
+        assert_eq!(selection.index(), 2);
+        selection.next();
+        assert_eq!(selection.index(), 0);
+    }
+
+    #[test]
+    fn empty_menu_selection_stays_at_zero() {
+        let mut selection = MenuSelection::new(0);
+        selection.next();
+        assert_eq!(selection.index(), 0);
+        selection.previous();
+        assert_eq!(selection.index(), 0);
+    }
+
+    /// move a fresh selection of 3 items once, as if only `held` was down, and return the new index.
+    fn navigate_with(bindings: &InputBindings, held: KeyboardKey) -> usize {
+        let mut selection = MenuSelection::new(3);
+        selection.navigate(|action| bindings.keys(action).contains(&held), 0.0);
+        selection.index()
+    }
+
+    #[test]
+    fn menu_navigation_follows_bindings() {
+        let mut bindings = InputBindings::default();
+        // by default the down arrow moves down.
+        assert_eq!(navigate_with(&bindings, KeyboardKey::KEY_DOWN), 1);
+
+        // with up and down swapped the down arrow moves up, wrapping to the last item.
+        bindings.set_binding(Action::Up, vec![KeyboardKey::KEY_DOWN]);
+        bindings.set_binding(Action::Down, vec![KeyboardKey::KEY_UP]);
+        assert_eq!(navigate_with(&bindings, KeyboardKey::KEY_DOWN), 2);
+        assert_eq!(navigate_with(&bindings, KeyboardKey::KEY_UP), 1);
+    }
+
+    /// a 200 by 40 button with its top left corner at (100, 50).
+    fn test_button() -> Button {
+        Button::new(Rectangle::new(100.0, 50.0, 200.0, 40.0), "Play", Color::GRAY, Color::BLACK)
+    }
+
+    #[test]
+    fn button_contains_points_inside_and_on_the_edge() {
+        let button = test_button();
+        assert!(button.contains(&Vector2::new(200.0, 70.0)));
+        assert!(button.contains(&Vector2::new(100.0, 50.0)));
+        assert!(button.contains(&Vector2::new(300.0, 90.0)));
+        assert!(!button.contains(&Vector2::new(99.0, 70.0)));
+        assert!(!button.contains(&Vector2::new(200.0, 91.0)));
+    }
+
+    #[test]
+    fn center_on_moves_the_button_and_keeps_its_size() {
+        let mut button = test_button();
+        button.center_on(400.0, 300.0);
+        assert_eq!(button.rect.width, 200.0);
+        assert_eq!(button.rect.height, 40.0);
+        assert_eq!(button.rect.x, 300.0);
+        assert_eq!(button.rect.y, 280.0);
+        // the label is drawn at the center, which is now where the button was centered.
+        assert_eq!(button.center(), Vector2::new(400.0, 300.0));
+    }
+}